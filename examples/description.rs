@@ -0,0 +1,40 @@
+#[allow(unused_imports)]
+#[macro_use]
+extern crate limn;
+
+mod util;
+
+use limn::prelude::*;
+
+use limn::description;
+use limn::input::mouse::ClickEvent;
+use limn::draw::text::TextState;
+
+static UI: &'static str = r##"
+{
+    "type": "widget",
+    "name": "root",
+    "container": { "linear_layout": { "orientation": "horizontal", "spacing": "around" } },
+    "constraints": [ { "min_size": [200, 100] } ],
+    "children": [
+        { "type": "text", "name": "count", "text": "0",
+          "constraints": [ { "center_vertical": "parent" } ] },
+        { "type": "button", "name": "increment", "text": "Count",
+          "constraints": [ { "center_vertical": "parent" } ] }
+    ]
+}
+"##;
+
+fn main() {
+    let app = util::init_default("Limn description demo");
+    let ui = description::load_str(UI).unwrap();
+
+    let mut count = ui.widget("count").unwrap();
+    let mut clicks = 0;
+    ui.widget("increment").unwrap().add_handler_fn(move |_: &ClickEvent, _| {
+        clicks += 1;
+        count.update(|state: &mut TextState| state.text = format!("{}", clicks));
+    });
+
+    app.main_loop(ui.root);
+}
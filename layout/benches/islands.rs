@@ -0,0 +1,114 @@
+// Compares solving a large layout tree in a single solver against isolating each panel,
+// run with `cargo bench`
+#[macro_use]
+extern crate limn_layout as layout;
+
+use std::time::{Duration, Instant};
+
+use layout::{LimnSolver, Layout, Size, Point};
+use layout::constraint::*;
+use layout::linear_layout::{LinearLayout, LinearLayoutSettings, Orientation, ItemAlignment};
+
+const NUM_PANELS: usize = 10;
+const ROWS_PER_PANEL: usize = 50;
+const NUM_EDITS: u32 = 100;
+
+struct Tree {
+    solver: LimnSolver,
+    root: Layout,
+    panels: Vec<Layout>,
+    rows: Vec<Vec<Layout>>,
+}
+
+impl Tree {
+    fn new(isolated: bool) -> Self {
+        let mut next_id = 0;
+        let mut new_layout = |name: String| {
+            next_id += 1;
+            Layout::new(next_id, Some(name))
+        };
+        let mut root = new_layout("root".to_owned());
+        root.add(constraints![
+            top_left(Point::new(0.0, 0.0)),
+            size(Size::new(2000.0, 1000.0)),
+        ]);
+        let mut settings = LinearLayoutSettings::new(Orientation::Horizontal);
+        settings.item_align = ItemAlignment::Fill;
+        settings.fill_equal = true;
+        let container = LinearLayout::new(&mut root, settings);
+        root.set_container(container);
+
+        let mut panels = Vec::new();
+        let mut rows = Vec::new();
+        for i in 0..NUM_PANELS {
+            let mut panel = new_layout(format!("panel_{}", i));
+            if isolated {
+                panel.isolate();
+            }
+            let mut settings = LinearLayoutSettings::new(Orientation::Vertical);
+            settings.item_align = ItemAlignment::Fill;
+            let container = LinearLayout::new(&mut panel, settings);
+            panel.set_container(container);
+            root.add_child(&mut panel);
+
+            let mut panel_rows = Vec::new();
+            for j in 0..ROWS_PER_PANEL {
+                let mut row = new_layout(format!("row_{}_{}", i, j));
+                row.edit_height().set(5.0);
+                panel.add_child(&mut row);
+                panel_rows.push(row);
+            }
+            panels.push(panel);
+            rows.push(panel_rows);
+        }
+        Tree {
+            solver: LimnSolver::new(),
+            root: root,
+            panels: panels,
+            rows: rows,
+        }
+    }
+
+    fn update(&mut self) {
+        self.solver.update_layout(&mut self.root);
+        for (panel, rows) in self.panels.iter_mut().zip(self.rows.iter_mut()) {
+            self.solver.update_layout(panel);
+            for row in rows {
+                self.solver.update_layout(row);
+            }
+        }
+        self.solver.fetch_changes();
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 / 1_000_000.0
+}
+
+fn bench(isolated: bool) {
+    let start = Instant::now();
+    let mut tree = Tree::new(isolated);
+    tree.update();
+    let build_time = start.elapsed();
+
+    // resize a single row in one panel
+    let start = Instant::now();
+    for i in 0..NUM_EDITS {
+        let height = if i % 2 == 0 { 6.0 } else { 5.0 };
+        let mut row = &mut tree.rows[0][0];
+        row.edit_height().set(height);
+        tree.solver.update_layout(&mut row);
+        tree.solver.fetch_changes();
+    }
+    let edit_time = start.elapsed();
+
+    println!("{:<12} build {:>10.3} ms, edit {:>8.3} ms/update",
+             if isolated { "isolated" } else { "single" },
+             millis(build_time), millis(edit_time) / NUM_EDITS as f64);
+}
+
+fn main() {
+    println!("{} panels with {} rows each", NUM_PANELS, ROWS_PER_PANEL);
+    bench(false);
+    bench(true);
+}
@@ -117,6 +117,39 @@ pub fn bound_bottom<T: LayoutRef>(outer: &T) -> PaddableConstraintBuilder {
     PaddableConstraint::BoundBottom(outer.bottom).builder(REQUIRED)
 }
 
+pub fn align_start<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+    let widget = widget.layout_ref();
+    PaddableConstraint::AlignStart(widget.start).builder(REQUIRED)
+}
+pub fn align_end<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+    let widget = widget.layout_ref();
+    PaddableConstraint::AlignEnd(widget.end).builder(REQUIRED)
+}
+pub fn align_to_start_of<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+    let widget = widget.layout_ref();
+    PaddableConstraint::AlignToStartOf(widget.start).builder(REQUIRED)
+}
+pub fn align_to_end_of<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+    let widget = widget.layout_ref();
+    PaddableConstraint::AlignToEndOf(widget.end).builder(REQUIRED)
+}
+pub fn to_start_of<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+    let widget = widget.layout_ref();
+    PaddableConstraint::ToStartOf(widget.start).builder(REQUIRED)
+}
+pub fn to_end_of<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+    let widget = widget.layout_ref();
+    PaddableConstraint::ToEndOf(widget.end).builder(REQUIRED)
+}
+pub fn bound_start<T: LayoutRef>(outer: &T) -> PaddableConstraintBuilder {
+    let outer = outer.layout_ref();
+    PaddableConstraint::BoundStart(outer.start).builder(REQUIRED)
+}
+pub fn bound_end<T: LayoutRef>(outer: &T) -> PaddableConstraintBuilder {
+    let outer = outer.layout_ref();
+    PaddableConstraint::BoundEnd(outer.end).builder(REQUIRED)
+}
+
 pub fn bound_by<T: LayoutRef>(outer: &T) -> PaddableConstraintBuilder {
     let outer = outer.layout_ref();
     PaddableConstraint::BoundBy(outer.clone()).builder(REQUIRED)
@@ -126,13 +159,21 @@ pub fn match_layout<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
     let widget = widget.layout_ref();
     PaddableConstraint::MatchLayout(widget.clone()).builder(REQUIRED)
 }
-pub fn match_width<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+pub fn match_width<T: LayoutRef>(widget: &T) -> ScalableConstraintBuilder {
     let widget = widget.layout_ref();
-    PaddableConstraint::MatchWidth(widget.width).builder(REQUIRED)
+    ScalableConstraint::MatchWidth(widget.width).builder(REQUIRED)
 }
-pub fn match_height<T: LayoutRef>(widget: &T) -> PaddableConstraintBuilder {
+pub fn match_height<T: LayoutRef>(widget: &T) -> ScalableConstraintBuilder {
     let widget = widget.layout_ref();
-    PaddableConstraint::MatchHeight(widget.height).builder(REQUIRED)
+    ScalableConstraint::MatchHeight(widget.height).builder(REQUIRED)
+}
+/// Width is `fraction` of the width of `widget`, eg. 0.4 for 40%
+pub fn width_fraction_of<T: LayoutRef>(widget: &T, fraction: f32) -> ScalableConstraintBuilder {
+    match_width(widget).multiplier(fraction)
+}
+/// Height is `fraction` of the height of `widget`, eg. 0.4 for 40%
+pub fn height_fraction_of<T: LayoutRef>(widget: &T, fraction: f32) -> ScalableConstraintBuilder {
+    match_height(widget).multiplier(fraction)
 }
 
 #[derive(Clone)]
@@ -171,8 +212,20 @@ pub enum PaddableConstraint {
     BoundTop(Variable),
     BoundRight(Variable),
     BoundBottom(Variable),
+    AlignStart(Variable),
+    AlignEnd(Variable),
+    AlignToStartOf(Variable),
+    AlignToEndOf(Variable),
+    ToStartOf(Variable),
+    ToEndOf(Variable),
+    BoundStart(Variable),
+    BoundEnd(Variable),
     BoundBy(LayoutVars),
     MatchLayout(LayoutVars),
+}
+
+#[derive(Clone)]
+pub enum ScalableConstraint {
     MatchWidth(Variable),
     MatchHeight(Variable),
 }
@@ -194,6 +247,18 @@ impl PaddableConstraint {
     }
 }
 
+impl ScalableConstraint {
+    pub fn builder(self, default_strength: f64) -> ScalableConstraintBuilder {
+        ScalableConstraintBuilder {
+            constraint: self,
+            strength: default_strength,
+            padding: 0.0,
+            multiplier: 1.0,
+            offset: 0.0,
+        }
+    }
+}
+
 pub struct WidgetConstraintBuilder {
     constraint: WidgetConstraint,
     strength: f64,
@@ -221,6 +286,33 @@ impl PaddableConstraintBuilder {
     }
 }
 
+/// Builds constraints of the form `widget = other * multiplier - padding + offset`
+pub struct ScalableConstraintBuilder {
+    constraint: ScalableConstraint,
+    strength: f64,
+    padding: f32,
+    multiplier: f32,
+    offset: f32,
+}
+impl ScalableConstraintBuilder {
+    pub fn strength(mut self, strength: f64) -> Self {
+        self.strength = strength;
+        self
+    }
+    pub fn padding(mut self, padding: f32) -> Self {
+        self.padding = padding;
+        self
+    }
+    pub fn multiplier(mut self, multiplier: f32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+    pub fn offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
 pub trait ConstraintBuilder {
     fn build(&self, widget: &LayoutVars) -> Vec<Constraint>;
 }
@@ -383,6 +475,30 @@ impl ConstraintBuilder for PaddableConstraintBuilder {
             PaddableConstraint::BoundBottom(bottom) => {
                 vec![ bottom - widget.bottom | GE(strength) | padding ]
             }
+            PaddableConstraint::AlignStart(start) => {
+                vec![ widget.start - start | EQ(strength) | padding ]
+            }
+            PaddableConstraint::AlignEnd(end) => {
+                vec![ end - widget.end | EQ(strength) | padding ]
+            }
+            PaddableConstraint::AlignToStartOf(start) => {
+                vec![ start - widget.end | EQ(strength) | padding ]
+            }
+            PaddableConstraint::AlignToEndOf(end) => {
+                vec![ widget.start - end | EQ(strength) | padding ]
+            }
+            PaddableConstraint::ToStartOf(start) => {
+                vec![ start - widget.end | GE(strength) | padding ]
+            }
+            PaddableConstraint::ToEndOf(end) => {
+                vec![ widget.start - end | GE(strength) | padding ]
+            }
+            PaddableConstraint::BoundStart(start) => {
+                vec![ widget.start - start | GE(strength) | padding ]
+            }
+            PaddableConstraint::BoundEnd(end) => {
+                vec![ end - widget.end | GE(strength) | padding ]
+            }
             PaddableConstraint::BoundBy(other) => {
                 vec![
                     widget.left - other.left | GE(strength) | padding,
@@ -399,11 +515,21 @@ impl ConstraintBuilder for PaddableConstraintBuilder {
                     other.bottom - widget.bottom | EQ(strength) | padding,
                 ]
             }
-            PaddableConstraint::MatchWidth(width) => {
-                vec![ width - widget.width | EQ(strength) | padding ]
+        }
+    }
+}
+
+impl ConstraintBuilder for ScalableConstraintBuilder {
+    fn build(&self, widget: &LayoutVars) -> Vec<Constraint> {
+        let strength = self.strength;
+        let multiplier = self.multiplier as f64;
+        let constant = self.padding - self.offset;
+        match self.constraint.clone() {
+            ScalableConstraint::MatchWidth(width) => {
+                vec![ width * multiplier - widget.width | EQ(strength) | constant ]
             }
-            PaddableConstraint::MatchHeight(height) => {
-                vec![ height - widget.height | EQ(strength) | padding ]
+            ScalableConstraint::MatchHeight(height) => {
+                vec![ height * multiplier - widget.height | EQ(strength) | constant ]
             }
         }
     }
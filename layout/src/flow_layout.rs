@@ -0,0 +1,187 @@
+use std::mem;
+
+use cassowary::strength::*;
+use cassowary::WeightedRelation::*;
+use cassowary::Constraint;
+
+use super::{LayoutId, LayoutVars, Layout, LayoutContainer, Rect};
+
+/// Specifies how each row is positioned horizontally within the container
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowAlignment {
+    /// Rows are packed against the left bound of the container
+    Start,
+    /// Rows are centered within the container
+    Center,
+    /// Rows are packed against the right bound of the container
+    End,
+}
+
+pub struct FlowLayoutSettings {
+    /// Space between adjacent items in a row
+    pub horizontal_gap: f32,
+    /// Space between rows
+    pub vertical_gap: f32,
+    pub row_align: RowAlignment,
+}
+
+impl FlowLayoutSettings {
+    pub fn new() -> Self {
+        FlowLayoutSettings {
+            horizontal_gap: 0.0,
+            vertical_gap: 0.0,
+            row_align: RowAlignment::Start,
+        }
+    }
+}
+
+/// Places children left to right, wrapping onto a new row when the width of the container
+/// runs out.
+///
+/// Row breaks can't be expressed as linear constraints, so they are recomputed from the solved
+/// bounds of the container and its children whenever they change, see `Layout::layout_changed`.
+/// Until then all children are placed in a single row.
+pub struct FlowLayout {
+    settings: FlowLayoutSettings,
+    children: Vec<(LayoutId, LayoutVars)>,
+    /// Number of children in each row
+    rows: Vec<usize>,
+    row_vars: Vec<LayoutVars>,
+    constraints: Vec<Constraint>,
+}
+
+impl FlowLayout {
+    pub fn new(settings: FlowLayoutSettings) -> Self {
+        FlowLayout {
+            settings: settings,
+            children: Vec::new(),
+            rows: Vec::new(),
+            row_vars: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    fn measure_rows(&self, width: f32, bounds: &Fn(LayoutId) -> Option<Rect>) -> Vec<usize> {
+        let gap = self.settings.horizontal_gap;
+        let mut rows = Vec::new();
+        let mut row_width = 0.0;
+        let mut row_len = 0;
+        for &(id, _) in &self.children {
+            let child_width = bounds(id).map(|rect| rect.size.width).unwrap_or(0.0);
+            if row_len > 0 && row_width + gap + child_width > width {
+                rows.push(row_len);
+                row_len = 0;
+            }
+            row_width = if row_len == 0 { child_width } else { row_width + gap + child_width };
+            row_len += 1;
+        }
+        if row_len > 0 {
+            rows.push(row_len);
+        }
+        rows
+    }
+
+    fn update_constraints(&mut self, parent: &mut Layout) {
+        parent.remove_constraints(mem::replace(&mut self.constraints, Vec::new()));
+        while self.row_vars.len() < self.rows.len() {
+            let row = LayoutVars::new();
+            parent.add_associated_vars(&row, &format!("flow_row_{}", self.row_vars.len()));
+            self.row_vars.push(row);
+        }
+        let (h_gap, v_gap) = (self.settings.horizontal_gap, self.settings.vertical_gap);
+        let parent_vars = &parent.vars;
+        let mut constraints = Vec::new();
+        let mut children = self.children.iter();
+        let mut last_row: Option<&LayoutVars> = None;
+        for (row, &row_len) in self.row_vars.iter().zip(self.rows.iter()) {
+            constraints.extend(vec![
+                row.right - row.left | EQ(REQUIRED) | row.width,
+                row.bottom - row.top | EQ(REQUIRED) | row.height,
+                row.height | EQ(WEAK) | 0.0,
+                row.left | GE(STRONG) | parent_vars.left,
+                row.right | LE(STRONG) | parent_vars.right,
+            ]);
+            match self.settings.row_align {
+                RowAlignment::Start => {
+                    constraints.push(row.left | EQ(REQUIRED) | parent_vars.left);
+                }
+                RowAlignment::Center => {
+                    constraints.push(row.left - parent_vars.left | EQ(REQUIRED) | parent_vars.right - row.right);
+                }
+                RowAlignment::End => {
+                    constraints.push(row.right | EQ(REQUIRED) | parent_vars.right);
+                }
+            }
+            if let Some(last_row) = last_row {
+                constraints.push(row.top | EQ(REQUIRED) | last_row.bottom + v_gap);
+            } else {
+                constraints.push(row.top | EQ(REQUIRED) | parent_vars.top);
+            }
+            let mut last_child: Option<&LayoutVars> = None;
+            for &(_, ref child) in children.by_ref().take(row_len) {
+                constraints.extend(vec![
+                    child.top | EQ(REQUIRED) | row.top,
+                    child.bottom | LE(REQUIRED) | row.bottom,
+                ]);
+                if let Some(last_child) = last_child {
+                    constraints.push(child.left | EQ(REQUIRED) | last_child.right + h_gap);
+                } else {
+                    constraints.push(child.left | EQ(REQUIRED) | row.left);
+                }
+                last_child = Some(child);
+            }
+            if let Some(last_child) = last_child {
+                constraints.push(last_child.right | EQ(REQUIRED) | row.right);
+            }
+            last_row = Some(row);
+        }
+        if let Some(last_row) = last_row {
+            constraints.push(last_row.bottom | LE(REQUIRED) | parent_vars.bottom);
+        }
+        parent.add(constraints.clone());
+        self.constraints = constraints;
+    }
+}
+
+impl LayoutContainer for FlowLayout {
+    fn add_child(&mut self, parent: &mut Layout, child: &mut Layout) {
+        self.children.push((child.id, child.vars.clone()));
+        if let Some(last_row) = self.rows.last_mut() {
+            *last_row += 1;
+        }
+        if self.rows.is_empty() {
+            self.rows.push(1);
+        }
+        self.update_constraints(parent);
+    }
+
+    fn remove_child(&mut self, parent: &mut Layout, child: &mut Layout) {
+        if let Some(pos) = self.children.iter().position(|&(id, _)| id == child.id) {
+            self.children.remove(pos);
+            let mut row_start = 0;
+            for row in 0..self.rows.len() {
+                if pos < row_start + self.rows[row] {
+                    self.rows[row] -= 1;
+                    if self.rows[row] == 0 {
+                        self.rows.remove(row);
+                    }
+                    break;
+                }
+                row_start += self.rows[row];
+            }
+            self.update_constraints(parent);
+        }
+    }
+
+    fn layout_changed(&mut self, parent: &mut Layout, bounds: &Fn(LayoutId) -> Option<Rect>) -> bool {
+        if let Some(parent_bounds) = bounds(parent.id) {
+            let rows = self.measure_rows(parent_bounds.size.width, bounds);
+            if rows != self.rows {
+                self.rows = rows;
+                self.update_constraints(parent);
+                return true;
+            }
+        }
+        false
+    }
+}
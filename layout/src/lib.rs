@@ -5,7 +5,7 @@ extern crate log;
 #[macro_use]
 extern crate lazy_static;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::Drop;
 use std::mem;
 use std::rc::Rc;
@@ -35,6 +35,11 @@ pub struct LayoutVars {
     pub bottom: Variable,
     pub width: Variable,
     pub height: Variable,
+    /// Logical edges along the reading direction, `start` is the left edge for left to right
+    /// layouts and the right edge for right to left layouts. Measured in the reading direction,
+    /// so `end - start` is always the width, see `Direction`.
+    pub start: Variable,
+    pub end: Variable,
 }
 impl LayoutVars {
     pub fn new() -> Self {
@@ -45,10 +50,12 @@ impl LayoutVars {
             bottom: Variable::new(),
             width: Variable::new(),
             height: Variable::new(),
+            start: Variable::new(),
+            end: Variable::new(),
         }
     }
-    pub fn array(&self) -> [Variable; 6] {
-        [self.left, self.top, self.right, self.bottom, self.width, self.height]
+    pub fn array(&self) -> [Variable; 8] {
+        [self.left, self.top, self.right, self.bottom, self.width, self.height, self.start, self.end]
     }
     pub fn var_type(&self, var: Variable) -> VarType {
         if var == self.left { VarType::Left }
@@ -57,8 +64,25 @@ impl LayoutVars {
         else if var == self.bottom { VarType::Bottom }
         else if var == self.width { VarType::Width }
         else if var == self.height { VarType::Height }
+        else if var == self.start { VarType::Start }
+        else if var == self.end { VarType::End }
         else { VarType::Other }
     }
+    /// Binds the logical edges to the physical ones for `direction`.
+    /// Required, so constraints on `start` and `end` that conflict with the physical edges are
+    /// reported as a `LayoutError` rather than moving the logical edges away from them.
+    fn direction_constraints(&self, direction: Direction) -> Vec<Constraint> {
+        match direction {
+            Direction::LeftToRight => vec![
+                self.start | EQ(REQUIRED) | self.left,
+                self.end | EQ(REQUIRED) | self.right,
+            ],
+            Direction::RightToLeft => vec![
+                self.start + self.right | EQ(REQUIRED) | 0.0,
+                self.end + self.left | EQ(REQUIRED) | 0.0,
+            ],
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -69,9 +93,18 @@ pub enum VarType {
     Bottom,
     Width,
     Height,
+    Start,
+    End,
     Other,
 }
 
+/// Reading direction of a layout, which decides where the logical `start` and `end` edges are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+}
+
 pub trait LayoutRef {
     fn layout_ref(&self) -> LayoutVars;
 }
@@ -105,7 +138,12 @@ pub struct Layout {
     removed_constraints: Vec<Constraint>,
     removed_children: Vec<LayoutId>,
     associated_vars: Vec<(Variable, String)>,
+    constraint_sets: HashMap<String, ConstraintSet>,
+    baseline: Option<Variable>,
+    direction: Direction,
+    direction_constraints: Vec<Constraint>,
     pub hidden: bool,
+    pub isolated: bool,
 }
 impl Layout {
     pub fn new(id: LayoutId, name: Option<String>) -> Self {
@@ -115,6 +153,8 @@ impl Layout {
         new_constraints.insert(vars.bottom - vars.top | EQ(REQUIRED) | vars.height);
         new_constraints.insert(vars.width | GE(REQUIRED) | 0.0);
         new_constraints.insert(vars.height | GE(REQUIRED) | 0.0);
+        let direction_constraints = vars.direction_constraints(Direction::LeftToRight);
+        new_constraints.extend(direction_constraints.iter().cloned());
         Layout {
             vars: vars,
             name: name,
@@ -128,7 +168,12 @@ impl Layout {
             removed_constraints: Vec::new(),
             removed_children: Vec::new(),
             associated_vars: Vec::new(),
+            constraint_sets: HashMap::new(),
+            baseline: None,
+            direction: Direction::LeftToRight,
+            direction_constraints: direction_constraints,
             hidden: false,
+            isolated: false,
         }
     }
     pub fn layout(&mut self) -> &mut Self {
@@ -183,6 +228,50 @@ impl Layout {
             }
         }
     }
+    /// Adds a named set of constraints, that can be enabled or disabled as a group.
+    /// New sets are disabled, unless they replace an enabled set with the same name.
+    pub fn add_set<B: ConstraintBuilder>(&mut self, name: &str, builder: B) {
+        let constraints = builder.build(&self.vars);
+        let enabled = self.is_set_enabled(name);
+        self.remove_set(name);
+        self.constraint_sets.insert(name.to_owned(), ConstraintSet {
+            constraints: constraints,
+            enabled: false,
+        });
+        if enabled {
+            self.enable_set(name);
+        }
+    }
+    pub fn remove_set(&mut self, name: &str) {
+        self.disable_set(name);
+        self.constraint_sets.remove(name);
+    }
+    pub fn enable_set(&mut self, name: &str) {
+        if let Some(set) = self.constraint_sets.get_mut(name) {
+            if !set.enabled {
+                set.enabled = true;
+                self.new_constraints.extend(set.constraints.iter().cloned());
+            }
+        }
+    }
+    pub fn disable_set(&mut self, name: &str) {
+        let constraints = match self.constraint_sets.get_mut(name) {
+            Some(ref mut set) if set.enabled => {
+                set.enabled = false;
+                set.constraints.clone()
+            }
+            _ => return,
+        };
+        self.remove_constraints(constraints);
+    }
+    /// Disables one set and enables another, the solver applies both changes in the same update
+    pub fn swap_sets(&mut self, disable: &str, enable: &str) {
+        self.disable_set(disable);
+        self.enable_set(enable);
+    }
+    pub fn is_set_enabled(&self, name: &str) -> bool {
+        self.constraint_sets.get(name).map_or(false, |set| set.enabled)
+    }
     pub fn has_constraint(&mut self, constraints: &Vec<Constraint>) -> bool {
         for constraint in constraints {
             if self.new_constraints.contains(constraint) || self.constraints.contains(constraint) {
@@ -224,6 +313,16 @@ impl Layout {
         }
         self.removed_children.push(child.id);
     }
+    /// Notifies the container that solved bounds have changed, `bounds` looks up
+    /// the current bounds of this layout or its children.
+    /// Returns true if the layout needs to be updated in the solver again.
+    pub fn layout_changed(&mut self, bounds: &Fn(LayoutId) -> Option<Rect>) -> bool {
+        if let Some(container) = self.container.clone() {
+            container.borrow_mut().layout_changed(self, bounds)
+        } else {
+            false
+        }
+    }
     pub fn get_removed_children(&mut self) -> Vec<LayoutId> {
         mem::replace(&mut self.removed_children, Vec::new())
     }
@@ -242,12 +341,58 @@ impl Layout {
     pub fn get_associated_vars(&mut self) -> Vec<(Variable, String)> {
         mem::replace(&mut self.associated_vars, Vec::new())
     }
+    /// Distance from the top of this layout to the baseline of its first line of text, used
+    /// to align text of different sizes. Created on first use, layouts without text default
+    /// to their bottom edge.
+    pub fn baseline(&mut self) -> Variable {
+        if let Some(baseline) = self.baseline {
+            return baseline;
+        }
+        let baseline = Variable::new();
+        self.add_associated_var(baseline, "baseline");
+        let height = self.vars.height;
+        self.add(vec![
+            baseline | GE(REQUIRED) | 0.0,
+            baseline | EQ(MEDIUM) | height,
+        ]);
+        self.baseline = Some(baseline);
+        baseline
+    }
+    /// Sets which physical edges the logical `start` and `end` edges of this layout are bound to.
+    /// Only affects this layout, descendants are expected to share the direction of their parent.
+    pub fn set_direction(&mut self, direction: Direction) {
+        if direction != self.direction {
+            let constraints = mem::replace(&mut self.direction_constraints, Vec::new());
+            self.remove_constraints(constraints);
+            let constraints = self.vars.direction_constraints(direction);
+            self.add(constraints.clone());
+            self.direction_constraints = constraints;
+            self.direction = direction;
+        }
+    }
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
     pub fn hide(&mut self) {
         self.hidden = true;
     }
     pub fn show(&mut self) {
         self.hidden = false;
     }
+    /// Solve the constraints of this layout's descendants in a separate solver.
+    /// The bounds of this layout are solved by the parent and are fixed from the point
+    /// of view of the descendants, so they can't affect its size or position.
+    ///
+    /// Constraints of the descendants may only refer to this layout and its descendants,
+    /// others are rejected with a `LayoutErrorKind::CrossesIsolation` error.
+    pub fn isolate(&mut self) {
+        self.isolated = true;
+    }
+}
+
+struct ConstraintSet {
+    constraints: Vec<Constraint>,
+    enabled: bool,
 }
 
 pub struct VariableEditable<'a> {
@@ -280,7 +425,7 @@ impl<'a> Drop for VariableEditable<'a> {
         self.builder.edit_vars.push(edit_var);
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EditVariable {
     var: Variable,
     val: f64,
@@ -316,9 +461,113 @@ macro_rules! constraints {
     };
 }
 
+/// Builds a constraint from a relation between linear expressions of layout variables,
+/// with an optional strength, which defaults to `REQUIRED`:
+///
+/// `layout!(a.left == b.right + 8 @ STRONG)`, `layout!(a.width >= 0.5 * b.width - (padding))`
+///
+/// Variables are written `widget.var`, where `widget` is an identifier or a parenthesized
+/// expression implementing `LayoutRef`, and `LAYOUT.var` refers to the widget the constraint is added to.
+/// Coefficients and constants are literals or parenthesized expressions.
+#[macro_export]
+macro_rules! layout {
+    (@split [$($lhs:tt)*] == $($rest:tt)*) => { layout!(@rhs [$($lhs)*] [Equal] [] $($rest)*) };
+    (@split [$($lhs:tt)*] <= $($rest:tt)*) => { layout!(@rhs [$($lhs)*] [LessOrEqual] [] $($rest)*) };
+    (@split [$($lhs:tt)*] >= $($rest:tt)*) => { layout!(@rhs [$($lhs)*] [GreaterOrEqual] [] $($rest)*) };
+    (@split [$($lhs:tt)*] < $($rest:tt)*) => { compile_error!("layout! doesn't support strict inequalities, use <=") };
+    (@split [$($lhs:tt)*] > $($rest:tt)*) => { compile_error!("layout! doesn't support strict inequalities, use >=") };
+    (@split [$($lhs:tt)*] = $($rest:tt)*) => { compile_error!("layout! expected a relation, use == instead of =") };
+    (@split [$($lhs:tt)*] $next:tt $($rest:tt)*) => { layout!(@split [$($lhs)* $next] $($rest)*) };
+    (@split [$($lhs:tt)*]) => { compile_error!("layout! expected a relation: ==, <= or >=") };
+
+    (@rhs [$($lhs:tt)*] [$op:ident] [$($rhs:tt)*] @ $strength:expr) => {
+        layout!(@build [$($lhs)*] [$op] [$($rhs)*] $strength)
+    };
+    (@rhs [$($lhs:tt)*] [$op:ident] [$($rhs:tt)*] $next:tt $($rest:tt)*) => {
+        layout!(@rhs [$($lhs)*] [$op] [$($rhs)* $next] $($rest)*)
+    };
+    (@rhs [$($lhs:tt)*] [$op:ident] [$($rhs:tt)*]) => {
+        layout!(@build [$($lhs)*] [$op] [$($rhs)*] $crate::dsl::REQUIRED)
+    };
+
+    (@build [] [$op:ident] [$($rhs:tt)*] $strength:expr) => { compile_error!("layout! relation is missing a left hand side") };
+    (@build [$($lhs:tt)*] [$op:ident] [] $strength:expr) => { compile_error!("layout! relation is missing a right hand side") };
+    (@build [$($lhs:tt)*] [$op:ident] [$($rhs:tt)*] $strength:expr) => {
+        $crate::dsl::constraint(
+            layout!(@expr [$crate::dsl::constant(0.0)] + $($lhs)*),
+            $crate::dsl::RelationalOperator::$op,
+            layout!(@expr [$crate::dsl::constant(0.0)] + $($rhs)*),
+            $strength)
+    };
+
+    (@expr [$acc:expr]) => { $acc };
+    (@expr [$acc:expr] + - $($rest:tt)*) => { layout!(@term [$acc] [-] $($rest)*) };
+    (@expr [$acc:expr] + $($rest:tt)*) => { layout!(@term [$acc] [+] $($rest)*) };
+    (@expr [$acc:expr] - $($rest:tt)*) => { layout!(@term [$acc] [-] $($rest)*) };
+    (@expr [$acc:expr] $($rest:tt)*) => {
+        compile_error!(concat!("layout! expected + or - before: ", stringify!($($rest)*)))
+    };
+
+    (@term [$acc:expr] [$sign:tt] $coef:literal * $widget:tt . $var:ident $($rest:tt)*) => {
+        layout!(@expr [$acc $sign $crate::dsl::term(layout!(@var $widget . $var), $coef as f64)] $($rest)*)
+    };
+    (@term [$acc:expr] [$sign:tt] ($coef:expr) * $widget:tt . $var:ident $($rest:tt)*) => {
+        layout!(@expr [$acc $sign $crate::dsl::term(layout!(@var $widget . $var), $coef as f64)] $($rest)*)
+    };
+    (@term [$acc:expr] [$sign:tt] $widget:tt . $var:ident * $coef:literal $($rest:tt)*) => {
+        layout!(@expr [$acc $sign $crate::dsl::term(layout!(@var $widget . $var), $coef as f64)] $($rest)*)
+    };
+    (@term [$acc:expr] [$sign:tt] $widget:tt . $var:ident * ($coef:expr) $($rest:tt)*) => {
+        layout!(@expr [$acc $sign $crate::dsl::term(layout!(@var $widget . $var), $coef as f64)] $($rest)*)
+    };
+    (@term [$acc:expr] [$sign:tt] $widget:tt . $var:ident $($rest:tt)*) => {
+        layout!(@expr [$acc $sign $crate::dsl::term(layout!(@var $widget . $var), 1.0)] $($rest)*)
+    };
+    (@term [$acc:expr] [$sign:tt] $constant:literal $($rest:tt)*) => {
+        layout!(@expr [$acc $sign $crate::dsl::constant($constant as f64)] $($rest)*)
+    };
+    (@term [$acc:expr] [$sign:tt] ($constant:expr) $($rest:tt)*) => {
+        layout!(@expr [$acc $sign $crate::dsl::constant($constant as f64)] $($rest)*)
+    };
+    (@term [$acc:expr] [$sign:tt] $($rest:tt)*) => {
+        compile_error!(concat!("layout! expected a variable, literal or parenthesized expression: ", stringify!($($rest)*)))
+    };
+
+    (@var LAYOUT . $var:ident) => { $crate::LAYOUT.$var };
+    (@var $widget:tt . $var:ident) => { $crate::dsl::layout_vars(&$widget).$var };
+
+    ($($tokens:tt)+) => { layout!(@split [] $($tokens)+) };
+}
+
+#[doc(hidden)]
+pub mod dsl {
+    pub use cassowary::RelationalOperator;
+    pub use cassowary::strength::REQUIRED;
+    use cassowary::{Variable, Expression, Constraint};
+    use super::{LayoutRef, LayoutVars};
+
+    pub fn layout_vars<T: LayoutRef>(widget: &T) -> LayoutVars {
+        widget.layout_ref()
+    }
+    pub fn term(var: Variable, coefficient: f64) -> Expression {
+        Expression::from_term(var * coefficient)
+    }
+    pub fn constant(constant: f64) -> Expression {
+        Expression::from_constant(constant)
+    }
+    pub fn constraint(lhs: Expression, op: RelationalOperator, rhs: Expression, strength: f64) -> Constraint {
+        Constraint::new(lhs - rhs, op, strength)
+    }
+}
+
 pub trait LayoutContainer {
     fn add_child(&mut self, parent: &mut Layout, child: &mut Layout);
     fn remove_child(&mut self, _: &mut Layout, _: &mut Layout) {}
+    /// Called after the solved bounds of the parent or its children change, for containers
+    /// whose constraints depend on solved values. Returns true if any constraints were modified.
+    fn layout_changed(&mut self, _: &mut Layout, _: &Fn(LayoutId) -> Option<Rect>) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -347,8 +596,9 @@ pub mod solver;
 pub mod constraint;
 pub mod linear_layout;
 pub mod grid_layout;
+pub mod flow_layout;
 
-pub use self::solver::LimnSolver;
+pub use self::solver::{LimnSolver, LayoutError, LayoutErrorKind};
 
 lazy_static! {
     pub static ref LAYOUT: LayoutVars = LayoutVars::new();
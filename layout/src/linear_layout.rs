@@ -34,12 +34,21 @@ pub enum ItemAlignment {
     /// For a vertical layout, align items to the parent's right bound.
     /// Do not use in a horizontal layout
     Right,
+    /// For a vertical layout, align items to the parent's start bound, see `Direction`.
+    /// Do not use in a horizontal layout
+    Start,
+    /// For a vertical layout, align items to the parent's end bound.
+    /// Do not use in a horizontal layout
+    End,
     /// For a horizontal layout, align items to the parent's top bound.
     /// Do not use in a vertical layout
     Top,
     /// For a horizontal layout, align items to the parent's bottom bound.
     /// Do not use in a vertical layout
     Bottom,
+    /// For a horizontal layout, align the first text baseline of all items, see `Layout::baseline`.
+    /// Do not use in a vertical layout
+    Baseline,
 }
 
 pub struct LinearLayoutSettings {
@@ -84,6 +93,7 @@ pub struct LinearLayout {
     end: Variable,
     space: Variable,
     size: Option<Variable>,
+    baseline: Option<Variable>,
 
     widgets: HashMap<LayoutId, WidgetData>,
     last_widget: Option<LayoutId>,
@@ -127,12 +137,22 @@ impl LinearLayout {
         } else {
             None
         };
+        let baseline = if let ItemAlignment::Baseline = settings.item_align {
+            let baseline = Variable::new();
+            parent.add_associated_var(baseline, "linear_layout_baseline");
+            // keep items as close to the top as the tallest item allows
+            parent.add(baseline | EQ(WEAK) | parent.vars.top);
+            Some(baseline)
+        } else {
+            None
+        };
         LinearLayout {
             settings: settings,
             start: start,
             end: end,
             space: space,
             size: size,
+            baseline: baseline,
             widgets: HashMap::new(),
             last_widget: None,
         }
@@ -211,6 +231,15 @@ impl LayoutContainer for LinearLayout {
                             align_bottom(parent),
                         ]);
                     },
+                    ItemAlignment::Baseline => {
+                        let child_baseline = child.baseline();
+                        let child_top = child.vars.top;
+                        child.add(constraints![
+                            child_top + child_baseline | EQ(REQUIRED) | self.baseline.unwrap(),
+                            bound_top(parent),
+                            bound_bottom(parent),
+                        ]);
+                    },
                     ItemAlignment::None => {
                         child.add(constraints![
                             bound_top(parent),
@@ -247,6 +276,18 @@ impl LayoutContainer for LinearLayout {
                             align_right(parent),
                         ]);
                     },
+                    ItemAlignment::Start => {
+                        child.add(constraints![
+                            align_start(parent),
+                            bound_end(parent),
+                        ]);
+                    },
+                    ItemAlignment::End => {
+                        child.add(constraints![
+                            bound_start(parent),
+                            align_end(parent),
+                        ]);
+                    },
                     ItemAlignment::None => {
                         child.add(constraints![
                             bound_left(parent),
@@ -293,15 +334,16 @@ impl LayoutContainer for LinearLayout {
     }
 }
 
+// horizontal layouts use the logical edges, so items run right to left in right to left layouts
 fn beginning(orientation: Orientation, layout: &LayoutVars) -> Variable {
     match orientation {
-        Orientation::Horizontal => layout.left,
+        Orientation::Horizontal => layout.start,
         Orientation::Vertical => layout.top,
     }
 }
 fn ending(orientation: Orientation, layout: &LayoutVars) -> Variable {
     match orientation {
-        Orientation::Horizontal => layout.right,
+        Orientation::Horizontal => layout.end,
         Orientation::Vertical => layout.bottom,
     }
 }
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::fmt::Write;
+use std::mem;
+use std::fmt::{self, Write};
 
 use cassowary;
 use cassowary::strength;
@@ -9,10 +10,63 @@ use cassowary::WeightedRelation::*;
 
 use super::{LayoutId, Layout, VarType, LayoutVars, EditVariable, Rect, Point, Size};
 
+/// Why a constraint could not be added to the solver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutErrorKind {
+    /// Conflicts with the required constraints already present
+    Unsatisfiable,
+    /// Belongs to the descendants of an isolated layout, but refers to layouts outside of it
+    CrossesIsolation,
+}
+
+/// Describes a constraint that could not be added to the solver.
+#[derive(Debug, Clone)]
+pub struct LayoutError {
+    pub kind: LayoutErrorKind,
+    pub constraint: Constraint,
+    /// Required constraints connected to the rejected constraint through shared variables
+    pub conflicting: Vec<Constraint>,
+    /// Names of the layouts whose variables are involved
+    pub layouts: Vec<String>,
+    pub constraint_desc: String,
+    pub conflicting_desc: Vec<String>,
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Failed to add constraint {}", self.constraint_desc)?;
+        if self.kind == LayoutErrorKind::CrossesIsolation {
+            write!(f, ", it refers to layouts outside of its isolated layout")?;
+        }
+        write!(f, " (layouts: {})", self.layouts.join(", "))?;
+        for desc in &self.conflicting_desc {
+            write!(f, "\n    {}", desc)?;
+        }
+        Ok(())
+    }
+}
+
+/// Separate solver for the descendants of an isolated layout. The variables of the isolated
+/// layout itself are solved in the parent island and copied in as edit variables.
+struct Island {
+    solver: cassowary::Solver,
+    inputs: HashMap<Variable, f64>,
+}
+
 pub struct LimnSolver {
     pub solver: cassowary::Solver,
     pub strict: bool,
     layouts: LayoutManager,
+    errors: Vec<LayoutError>,
+    // required constraints that conflicted when added, retried in `fetch_changes` since they may
+    // only conflict with constraints that are replaced later in the same pass
+    unsatisfiable: Vec<Constraint>,
+    // islands whose solver was recreated since the last `fetch_changes`, see `rebuild_solver`
+    rebuilt: HashSet<Option<LayoutId>>,
+    islands: HashMap<LayoutId, Island>,
+    // island containing each constraint or edit variable that has been added, None being the main solver
+    constraint_islands: HashMap<Constraint, Option<LayoutId>>,
+    edit_var_islands: HashMap<Variable, Option<LayoutId>>,
 }
 
 impl LimnSolver {
@@ -21,6 +75,12 @@ impl LimnSolver {
             solver: cassowary::Solver::new(),
             strict: false,
             layouts: LayoutManager::new(),
+            errors: Vec::new(),
+            unsatisfiable: Vec::new(),
+            rebuilt: HashSet::new(),
+            islands: HashMap::new(),
+            constraint_islands: HashMap::new(),
+            edit_var_islands: HashMap::new(),
         }
     }
 
@@ -31,6 +91,15 @@ impl LimnSolver {
             self.layouts.register_layout(layout);
         }
         self.layouts.update_layout(layout);
+        let island_changed = {
+            let internal_layout = &self.layouts.layouts[&layout.id];
+            internal_layout.parent != layout.parent || internal_layout.isolated != layout.isolated
+        };
+        if !registered || island_changed {
+            self.layouts.set_parent(layout.id, layout.parent);
+            self.layouts.layouts.get_mut(&layout.id).unwrap().isolated = layout.isolated;
+            self.update_islands(layout.id);
+        }
 
         for child in layout.get_removed_children() {
             self.remove_layout(child);
@@ -62,36 +131,213 @@ impl LimnSolver {
     }
     fn update_edit_var(&mut self, edit_var: &EditVariable) {
         let &EditVariable { var, val, strength } = edit_var;
-        if !self.solver.has_edit_variable(&var) {
-            debug!("add edit_var {}", self.layouts.fmt_variable(var));
-            self.solver.add_edit_variable(var, strength).unwrap();
+        let island = self.layouts.var_island(var);
+        if let Some(prev_island) = self.edit_var_islands.insert(var, island) {
+            if prev_island != island {
+                if let Some(solver) = self.island_solver_mut(prev_island) {
+                    if solver.has_edit_variable(&var) {
+                        solver.remove_edit_variable(var).unwrap();
+                    }
+                }
+            }
+        }
+        let var_desc = self.layouts.fmt_variable(var);
+        let solver = self.island_solver_mut(island).unwrap();
+        if !solver.has_edit_variable(&var) {
+            debug!("add edit_var {}", var_desc);
+            solver.add_edit_variable(var, strength).unwrap();
         }
         if val.is_finite() {
-            self.solver.suggest_value(var, val).unwrap();
-            debug!("suggest edit_var {} {}", self.layouts.fmt_variable(var), val);
+            solver.suggest_value(var, val).unwrap();
+            debug!("suggest edit_var {} {}", var_desc, val);
         } else {
-            debug!("invalid edit_var {} {}", self.layouts.fmt_variable(var), val);
+            debug!("invalid edit_var {} {}", var_desc, val);
         }
     }
     fn add_constraint(&mut self, constraint: Constraint) {
         debug!("adding constraint {}", self.layouts.fmt_constraint(&constraint));
-        if self.solver.add_constraint(constraint.clone()).is_err() {
-            eprintln!("Failed to add constraint {}", self.layouts.fmt_constraint(&constraint));
-            self.debug_associated_constraints(&constraint);
-            if self.strict {
-                panic!("Solver unsatisfiable");
+        let island = self.layouts.constraint_island(&constraint);
+        // an island can't affect the variables outside of it, so these would be silently ignored
+        if self.layouts.crosses_island(&constraint, island) {
+            self.report_error(LayoutErrorKind::CrossesIsolation, constraint);
+        } else if self.island_solver_mut(island).unwrap().add_constraint(constraint.clone()).is_err() {
+            self.rebuild_solver(island);
+            if !self.unsatisfiable.contains(&constraint) {
+                self.unsatisfiable.push(constraint);
+            }
+        } else {
+            self.constraint_islands.insert(constraint, island);
+        }
+    }
+
+    // adds the constraints that were unsatisfiable when they were added, now that all of the
+    // updates before `fetch_changes` are applied, reporting those that still conflict
+    fn retry_unsatisfiable(&mut self) {
+        for constraint in mem::replace(&mut self.unsatisfiable, Vec::new()) {
+            let island = self.layouts.constraint_island(&constraint);
+            if self.island_solver_mut(island).unwrap().add_constraint(constraint.clone()).is_err() {
+                self.rebuild_solver(island);
+                self.report_error(LayoutErrorKind::Unsatisfiable, constraint);
+            } else {
+                self.constraint_islands.insert(constraint, island);
+            }
+        }
+    }
+
+    // cassowary can leave its tableau inconsistent after failing to add a constraint, making
+    // later constraints fail too, so the solver is recreated from what was added successfully
+    fn rebuild_solver(&mut self, island: Option<LayoutId>) {
+        let mut solver = cassowary::Solver::new();
+        if let Some(id) = island {
+            let inputs = &self.islands[&id].inputs;
+            for var in self.layouts.layouts[&id].vars.array().iter() {
+                solver.add_edit_variable(*var, REQUIRED - 1.0).unwrap();
+                if let Some(val) = inputs.get(var) {
+                    solver.suggest_value(*var, *val).unwrap();
+                }
+            }
+        }
+        for (constraint, constraint_island) in &self.constraint_islands {
+            if *constraint_island == island {
+                solver.add_constraint(constraint.clone()).unwrap();
             }
         }
+        for (var, edit_var_island) in &self.edit_var_islands {
+            if *edit_var_island == island {
+                let edit_var = self.layouts.var_ids.get(var)
+                    .and_then(|layout_id| self.layouts.layouts[layout_id].edit_vars.get(var));
+                if let Some(edit_var) = edit_var {
+                    solver.add_edit_variable(*var, edit_var.strength).unwrap();
+                    if edit_var.val.is_finite() {
+                        solver.suggest_value(*var, edit_var.val).unwrap();
+                    }
+                }
+            }
+        }
+        *self.island_solver_mut(island).unwrap() = solver;
+        self.rebuilt.insert(island);
+    }
+
+    fn report_error(&mut self, kind: LayoutErrorKind, constraint: Constraint) {
+        let error = self.layout_error(kind, constraint);
+        debug!("{}", error);
+        if self.strict {
+            panic!("Solver unsatisfiable: {}", error);
+        }
+        self.errors.push(error);
+    }
+
+    fn layout_error(&self, kind: LayoutErrorKind, constraint: Constraint) -> LayoutError {
+        let conflicting: Vec<Constraint> = self.associated_constraints(&constraint).into_iter()
+            .filter(|associated| *associated != constraint).collect();
+        let mut layouts = Vec::new();
+        for associated in conflicting.iter().chain(Some(&constraint)) {
+            for layout_id in self.layouts.dependent_layouts(associated) {
+                let name = self.layouts.layout_name(layout_id);
+                if !layouts.contains(&name) {
+                    layouts.push(name);
+                }
+            }
+        }
+        LayoutError {
+            kind: kind,
+            constraint_desc: self.layouts.fmt_constraint(&constraint),
+            conflicting_desc: conflicting.iter().map(|constraint| self.layouts.fmt_constraint(constraint)).collect(),
+            constraint: constraint,
+            conflicting: conflicting,
+            layouts: layouts,
+        }
+    }
+
+    /// Constraints that could not be added since the last call, because they conflict with
+    /// required constraints or cross an isolated layout. Empty when `strict` is set, since failures panic instead.
+    ///
+    /// Conflicts are only reported by `fetch_changes`, so constraints replacing each other across
+    /// several layouts, such as when changing the direction of a subtree, can be updated in any order.
+    pub fn take_errors(&mut self) -> Vec<LayoutError> {
+        mem::replace(&mut self.errors, Vec::new())
     }
 
     fn remove_constraint(&mut self, constraint: &Constraint) {
         debug!("removing constraint {}", self.layouts.fmt_constraint(constraint));
-        if self.solver.has_constraint(constraint) {
-            self.solver.remove_constraint(constraint).unwrap();
+        self.unsatisfiable.retain(|unsatisfiable| unsatisfiable != constraint);
+        if let Some(island) = self.constraint_islands.remove(constraint) {
+            if let Some(solver) = self.island_solver_mut(island) {
+                if solver.has_constraint(constraint) {
+                    solver.remove_constraint(constraint).unwrap();
+                }
+            }
+        }
+    }
+
+    fn island_solver(&self, island: Option<LayoutId>) -> Option<&cassowary::Solver> {
+        match island {
+            Some(id) => self.islands.get(&id).map(|island| &island.solver),
+            None => Some(&self.solver),
+        }
+    }
+    fn island_solver_mut(&mut self, island: Option<LayoutId>) -> Option<&mut cassowary::Solver> {
+        match island {
+            Some(id) => self.islands.get_mut(&id).map(|island| &mut island.solver),
+            None => Some(&mut self.solver),
+        }
+    }
+
+    // assigns a layout and its descendants to islands, creating or removing islands for isolated layouts,
+    // and moves any constraints and edit variables that now belong to a different island
+    fn update_islands(&mut self, id: LayoutId) {
+        let mut subtree = Vec::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let island = self.layouts.parent_island(id);
+            let isolated = {
+                let layout = self.layouts.layouts.get_mut(&id).unwrap();
+                layout.island = island;
+                layout.isolated
+            };
+            if isolated && !self.islands.contains_key(&id) {
+                let mut solver = cassowary::Solver::new();
+                for var in self.layouts.layouts[&id].vars.array().iter() {
+                    solver.add_edit_variable(*var, REQUIRED - 1.0).unwrap();
+                }
+                self.islands.insert(id, Island { solver: solver, inputs: HashMap::new() });
+            } else if !isolated {
+                self.islands.remove(&id);
+            }
+            subtree.push(id);
+            stack.extend(self.layouts.child_ids(id));
+        }
+        for id in subtree {
+            let (constraints, edit_vars) = {
+                let layout = &self.layouts.layouts[&id];
+                let constraints: Vec<Constraint> = layout.constraints.iter()
+                    .chain(layout.hidden_constraints.iter()).cloned().collect();
+                let edit_vars: Vec<EditVariable> = layout.edit_vars.values().cloned().collect();
+                (constraints, edit_vars)
+            };
+            for constraint in constraints {
+                let moved = match self.constraint_islands.get(&constraint) {
+                    Some(island) => *island != self.layouts.constraint_island(&constraint),
+                    None => false,
+                };
+                if moved {
+                    self.remove_constraint(&constraint);
+                    self.add_constraint(constraint);
+                }
+            }
+            for edit_var in edit_vars {
+                if self.edit_var_islands.get(&edit_var.var) != Some(&self.layouts.var_island(edit_var.var)) {
+                    self.update_edit_var(&edit_var);
+                }
+            }
         }
     }
 
     pub fn remove_layout(&mut self, id: LayoutId) {
+        if self.layouts.layouts.contains_key(&id) {
+            self.layouts.set_parent(id, None);
+        }
+        self.islands.remove(&id);
         if let Some(layout) = self.layouts.layouts.remove(&id) {
             for constraint in layout.constraints {
                 self.remove_constraint(&constraint);
@@ -133,7 +379,7 @@ impl LimnSolver {
                 self.remove_constraint(&constraint);
             }
             for constraint in self.layouts.layouts[&id].constraints.clone() {
-                if !self.solver.has_constraint(&constraint) {
+                if !self.has_constraint(&constraint) {
                     let mut hidden = false;
                     for layout_id in self.layouts.dependent_layouts(&constraint) {
                         if layout_id != id && self.layouts.layout_hidden(layout_id) {
@@ -154,30 +400,78 @@ impl LimnSolver {
             self.unhide_layout(child);
         }
     }
-    pub fn update_solver<F>(&mut self, f: F)
+    /// Runs `f` on the solver containing the variables of the layout `id`, which is the solver of
+    /// the nearest isolated ancestor's island, or the main solver for unregistered layouts.
+    pub fn update_solver<F>(&mut self, id: LayoutId, f: F)
         where F: Fn(&mut cassowary::Solver)
     {
-        f(&mut self.solver);
+        let island = self.layouts.layouts.get(&id).and_then(|layout| layout.island);
+        f(self.island_solver_mut(island).unwrap());
     }
 
     pub fn has_edit_variable(&mut self, v: &Variable) -> bool {
-        self.solver.has_edit_variable(v)
+        let island = self.layouts.var_island(*v);
+        self.island_solver(island).map_or(false, |solver| solver.has_edit_variable(v))
     }
     pub fn has_constraint(&self, constraint: &Constraint) -> bool {
-        self.solver.has_constraint(constraint)
+        match self.constraint_islands.get(constraint) {
+            Some(island) => self.island_solver(*island).map_or(false, |solver| solver.has_constraint(constraint)),
+            None => false,
+        }
+    }
+    fn get_value(&self, var: Variable) -> f64 {
+        let island = self.layouts.var_island(var);
+        self.island_solver(island).map_or(0.0, |solver| solver.get_value(var))
     }
 
     pub fn fetch_changes(&mut self) -> Vec<(LayoutId, VarType, f64)> {
+        self.retry_unsatisfiable();
         let mut changes = Vec::new();
-        for &(var, val) in self.solver.fetch_changes() {
-            debug!("solver {} = {}", self.layouts.fmt_variable(var), val);
-            if let Some(layout_id) = self.layouts.var_ids.get(&var) {
-                let var_type = self.layouts.layouts[&layout_id].vars.var_type(var);
-                changes.push((*layout_id, var_type, val));
+        let solver_changes = self.solver_changes(None);
+        self.push_changes(None, solver_changes, &mut changes);
+
+        // islands are solved after the island containing their isolated layout
+        let mut islands: Vec<LayoutId> = self.islands.keys().cloned().collect();
+        islands.sort_by_key(|id| self.layouts.island_depth(Some(*id)));
+        for id in islands {
+            let parent_island = self.layouts.layouts[&id].island;
+            for var in self.layouts.layouts[&id].vars.array().iter() {
+                let val = self.island_solver(parent_island).unwrap().get_value(*var);
+                let island = self.islands.get_mut(&id).unwrap();
+                if island.inputs.get(var) != Some(&val) {
+                    island.solver.suggest_value(*var, val).unwrap();
+                    island.inputs.insert(*var, val);
+                }
             }
+            let solver_changes = self.solver_changes(Some(id));
+            self.push_changes(Some(id), solver_changes, &mut changes);
         }
+        self.rebuilt.clear();
         changes
     }
+    fn solver_changes(&mut self, island: Option<LayoutId>) -> Vec<(Variable, f64)> {
+        let solver_changes = self.island_solver_mut(island).unwrap().fetch_changes().to_vec();
+        if self.rebuilt.contains(&island) {
+            // a new solver only reports the values that differ from zero, not from the previous solver
+            let solver = self.island_solver(island).unwrap();
+            self.layouts.var_ids.keys().map(|var| (*var, solver.get_value(*var))).collect()
+        } else {
+            solver_changes
+        }
+    }
+    fn push_changes(&self, island: Option<LayoutId>, solver_changes: Vec<(Variable, f64)>,
+                    changes: &mut Vec<(LayoutId, VarType, f64)>) {
+        for (var, val) in solver_changes {
+            if let Some(layout_id) = self.layouts.var_ids.get(&var) {
+                // the variables of isolated layouts are also present in their island, as inputs
+                if self.layouts.var_island(var) == island {
+                    debug!("solver {} = {}", self.layouts.fmt_variable(var), val);
+                    let var_type = self.layouts.layouts[&layout_id].vars.var_type(var);
+                    changes.push((*layout_id, var_type, val));
+                }
+            }
+        }
+    }
 
     pub fn debug_variables(&self) {
         println!("VARIABLES");
@@ -194,7 +488,7 @@ impl LimnSolver {
         while let Some(layout) = layouts.pop_front() {
             println!("{}", self.layouts.layout_name(layout).to_uppercase());
             for constraint in &self.layouts.layouts[&layout].constraints {
-                if !shown_constraints.contains(constraint) && self.solver.has_constraint(constraint) {
+                if !shown_constraints.contains(constraint) && self.has_constraint(constraint) {
                     self.debug_constraint(constraint);
                     shown_constraints.insert(constraint.clone());
                 }
@@ -212,6 +506,13 @@ impl LimnSolver {
     }
 
     pub fn debug_associated_constraints(&self, constraint: &Constraint) {
+        for constraint in self.associated_constraints(constraint) {
+            self.debug_constraint(&constraint);
+        }
+    }
+
+    // all required constraints reachable from `constraint` through shared variables
+    fn associated_constraints(&self, constraint: &Constraint) -> Vec<Constraint> {
         let mut visited_constraints = HashSet::new();
         let mut new_constraints = HashSet::new();
         new_constraints.insert(constraint.clone());
@@ -226,7 +527,7 @@ impl LimnSolver {
                     for constraint in self.layouts.constraints_for(var) {
                         if constraint.strength() >= REQUIRED &&
                             !visited_constraints.contains(constraint) &&
-                                self.solver.has_constraint(&constraint) {
+                                self.has_constraint(&constraint) {
                             newer_constraints.insert(constraint.clone());
                         }
                     }
@@ -235,9 +536,85 @@ impl LimnSolver {
             }
             new_constraints = newer_constraints;
         }
-        for constraint in visited_constraints {
-            self.debug_constraint(&constraint);
+        visited_constraints.into_iter().collect()
+    }
+
+    /// Formats the layouts, variables and constraints currently in the solver as a Graphviz DOT graph.
+    /// Layouts are drawn as clusters nested by the layout hierarchy, constraints between two
+    /// variables as edges labeled with relation and strength, and any other constraints as nodes.
+    pub fn dot_graph(&self) -> String {
+        let mut out = String::new();
+        let mut var_nodes = HashMap::new();
+        writeln!(out, "digraph layout {{").unwrap();
+        writeln!(out, "    node [shape=box];").unwrap();
+
+        // layouts that aren't reachable from the root are drawn as separate clusters
+        let mut layout_ids: Vec<LayoutId> = self.layouts.layouts.keys().cloned().collect();
+        layout_ids.sort();
+        layout_ids.insert(0, self.layouts.root);
+        let mut visited = HashSet::new();
+        for id in layout_ids {
+            if self.layouts.layouts.contains_key(&id) && visited.insert(id) {
+                self.dot_cluster(&mut out, id, 1, &mut visited, &mut var_nodes);
+            }
+        }
+
+        let mut shown_constraints = HashSet::new();
+        let mut layout_ids: Vec<&LayoutId> = self.layouts.layouts.keys().collect();
+        layout_ids.sort();
+        for id in layout_ids {
+            for constraint in &self.layouts.layouts[id].constraints {
+                if !self.has_constraint(constraint) || !shown_constraints.insert(constraint.clone()) {
+                    continue;
+                }
+                let desc = dot_escape(&self.layouts.fmt_constraint(constraint));
+                let label = format!("{} {}", constraint.op(), strength_desc(constraint.strength()).trim());
+                let mut vars = constraint_vars(constraint);
+                vars.dedup();
+                if vars.len() == 2 {
+                    writeln!(out, "    {} -> {} [label=\"{}\", tooltip=\"{}\"];",
+                             var_nodes[&vars[0]], var_nodes[&vars[1]], label, desc).unwrap();
+                } else {
+                    let node = format!("c{}", shown_constraints.len());
+                    writeln!(out, "    {} [shape=plaintext, label=\"{}\"];", node, desc).unwrap();
+                    for var in vars {
+                        writeln!(out, "    {} -> {} [style=dashed, arrowhead=none];", var_nodes[&var], node).unwrap();
+                    }
+                }
+            }
+        }
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    fn dot_cluster(&self, out: &mut String, id: LayoutId, depth: usize,
+                   visited: &mut HashSet<LayoutId>, var_nodes: &mut HashMap<Variable, String>) {
+        let indent = "    ".repeat(depth);
+        let layout = &self.layouts.layouts[&id];
+        writeln!(out, "{}subgraph cluster_{} {{", indent, id).unwrap();
+        writeln!(out, "{}    label=\"{}\";", indent, dot_escape(&self.layouts.layout_name(id))).unwrap();
+
+        let mut vars: Vec<(Variable, String)> = layout.vars.array().iter()
+            .map(|var| (*var, format!("{:?}", layout.vars.var_type(*var)).to_lowercase())).collect();
+        let mut associated_vars: Vec<(Variable, String)> = layout.associated_vars.iter()
+            .map(|(var, name)| (*var, name.clone())).collect();
+        associated_vars.sort_by(|a, b| a.1.cmp(&b.1));
+        vars.extend(associated_vars);
+        for (var, name) in vars {
+            let node = format!("v{}", var_nodes.len());
+            let mut label = dot_escape(&format!("{} = {}", name, self.get_value(var)));
+            if let Some(edit_var) = layout.edit_vars.get(&var) {
+                write!(label, "\\nedit {} {}", strength_desc(edit_var.strength).trim(), edit_var.val).unwrap();
+            }
+            writeln!(out, "{}    {} [label=\"{}\"];", indent, node, label).unwrap();
+            var_nodes.insert(var, node);
+        }
+        for child in self.layouts.children(id) {
+            if self.layouts.layouts.contains_key(&child) && visited.insert(child) {
+                self.dot_cluster(out, child, depth + 1, visited, var_nodes);
+            }
         }
+        writeln!(out, "{}}}", indent).unwrap();
     }
 
     pub fn debug_layouts(&self) {
@@ -252,7 +629,7 @@ impl LimnSolver {
 
     pub fn debug_layout(&self, id: LayoutId) {
         let bounds = {
-            let get_val = |var| self.solver.get_value(var) as f32;
+            let get_val = |var| self.get_value(var) as f32;
             let vars = &self.layouts.layouts[&id].vars;
             let origin = Point::new(get_val(vars.left), get_val(vars.top));
             let size = Size::new(get_val(vars.width), get_val(vars.height));
@@ -275,6 +652,10 @@ struct LayoutInternal {
     children: Vec<LayoutId>,
     hidden: bool,
     hidden_constraints: Vec<Constraint>,
+    parent: Option<LayoutId>,
+    isolated: bool,
+    // isolated layout whose island contains this layout, None for the main solver
+    island: Option<LayoutId>,
 }
 pub struct LayoutManager {
     root: LayoutId,
@@ -284,6 +665,8 @@ pub struct LayoutManager {
 
     pending_constraints: HashMap<Variable, Vec<Constraint>>,
     missing_vars: HashMap<Constraint, usize>,
+    // children by the parent set on each layout, registered or not
+    child_ids: HashMap<LayoutId, HashSet<LayoutId>>,
 }
 
 impl LayoutManager {
@@ -296,6 +679,7 @@ impl LayoutManager {
             constraints: HashMap::new(),
             pending_constraints: HashMap::new(),
             missing_vars: HashMap::new(),
+            child_ids: HashMap::new(),
         }
     }
 
@@ -314,6 +698,9 @@ impl LayoutManager {
             children: layout.children.clone(),
             hidden: false,
             hidden_constraints: Vec::new(),
+            parent: None,
+            isolated: false,
+            island: None,
         };
         self.layouts.insert(id, layout);
     }
@@ -327,6 +714,65 @@ impl LayoutManager {
         internal_layout.name = layout.name.clone();
     }
 
+    fn set_parent(&mut self, id: LayoutId, parent: Option<LayoutId>) {
+        if let Some(prev_parent) = self.layouts[&id].parent {
+            if let Some(children) = self.child_ids.get_mut(&prev_parent) {
+                children.remove(&id);
+            }
+        }
+        if let Some(parent) = parent {
+            self.child_ids.entry(parent).or_insert_with(HashSet::new).insert(id);
+        }
+        self.layouts.get_mut(&id).unwrap().parent = parent;
+    }
+    fn child_ids(&self, id: LayoutId) -> Vec<LayoutId> {
+        self.child_ids.get(&id).map(|children| children.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    // island that the children of a layout belong to
+    fn child_island(&self, id: LayoutId) -> Option<LayoutId> {
+        let layout = &self.layouts[&id];
+        if layout.isolated { Some(id) } else { layout.island }
+    }
+    fn parent_island(&self, id: LayoutId) -> Option<LayoutId> {
+        match self.layouts[&id].parent {
+            Some(parent) if self.layouts.contains_key(&parent) => self.child_island(parent),
+            _ => None,
+        }
+    }
+    // variables of a layout belong to the island containing it,
+    // associated variables of an isolated layout belong to its own island
+    fn var_island(&self, var: Variable) -> Option<LayoutId> {
+        if let Some(id) = self.var_ids.get(&var) {
+            let layout = &self.layouts[id];
+            if let VarType::Other = layout.vars.var_type(var) {
+                return self.child_island(*id);
+            }
+            layout.island
+        } else {
+            None
+        }
+    }
+    fn island_depth(&self, island: Option<LayoutId>) -> usize {
+        match island {
+            Some(id) => 1 + self.island_depth(self.layouts[&id].island),
+            None => 0,
+        }
+    }
+    // constraints are added to the most deeply nested island of any of their variables
+    fn constraint_island(&self, constraint: &Constraint) -> Option<LayoutId> {
+        constraint_vars(constraint).iter().map(|var| self.var_island(*var))
+            .max_by_key(|island| self.island_depth(*island)).unwrap_or(None)
+    }
+
+    // whether a constraint in `island` refers to variables solved in another island,
+    // other than those of the isolated layout itself, which are copied in as inputs
+    fn crosses_island(&self, constraint: &Constraint, island: Option<LayoutId>) -> bool {
+        constraint_vars(constraint).iter().any(|var| {
+            self.var_island(*var) != island && island.is_some() && self.var_ids.get(var) != island.as_ref()
+        })
+    }
+
     pub fn add_constraint(&mut self, constraint: &Constraint) -> bool {
         let mut missing_layouts = false;
         for term in &constraint.expr().terms {
@@ -474,6 +920,10 @@ impl LayoutManager {
     }
 }
 
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 fn strength_desc(strength: f64) -> &'static str {
     if strength < strength::WEAK { "WEAK-" }
     else if strength == strength::WEAK { "WEAK " }
@@ -11,7 +11,7 @@ use std::ops::{Deref, DerefMut};
 
 use cassowary::strength::*;
 
-use layout::{LimnSolver, LayoutId, Layout, VarType, LayoutRef, LayoutVars};
+use layout::{LimnSolver, LayoutErrorKind, LayoutId, Layout, VarType, LayoutRef, LayoutVars, LAYOUT};
 use layout::{Size, Point, Rect};
 use layout::constraint::*;
 
@@ -187,6 +187,155 @@ fn linear_layout_fill() {
     });
 }
 
+#[test]
+fn linear_layout_baseline() {
+    use cassowary::WeightedRelation::*;
+    use layout::linear_layout::{LinearLayout, LinearLayoutSettings, Orientation, ItemAlignment};
+
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut large_text = layout.new_widget("large_text");
+    let mut small_text = layout.new_widget("small_text");
+    let mut no_text = layout.new_widget("no_text");
+
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(300.0, 100.0))
+    ]);
+    let mut settings = LinearLayoutSettings::new(Orientation::Horizontal);
+    settings.item_align = ItemAlignment::Baseline;
+    let linear_layout = LinearLayout::new(&mut *root, settings);
+    root.set_container(linear_layout);
+
+    let baseline = large_text.baseline();
+    large_text.add(constraints![
+        size(Size::new(50.0, 30.0)),
+        baseline | EQ(REQUIRED) | 24.0,
+    ]);
+    let baseline = small_text.baseline();
+    small_text.add(constraints![
+        size(Size::new(50.0, 20.0)),
+        baseline | EQ(REQUIRED) | 14.0,
+    ]);
+    no_text.add(size(Size::new(50.0, 10.0)));
+
+    root.add_child(large_text.deref_mut());
+    root.add_child(small_text.deref_mut());
+    root.add_child(no_text.deref_mut());
+
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.layout_rects == hashmap!{
+        root.id => Rect::new(Point::new(0.0, 0.0), Size::new(300.0, 100.0)),
+        large_text.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 30.0)),
+        small_text.id => Rect::new(Point::new(50.0, 10.0), Size::new(50.0, 20.0)),
+        no_text.id => Rect::new(Point::new(100.0, 14.0), Size::new(50.0, 10.0)),
+    });
+}
+
+#[test]
+fn linear_layout_rtl() {
+    use layout::Direction;
+    use layout::linear_layout::{LinearLayout, LinearLayoutSettings, Orientation, ItemAlignment};
+
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut item_1 = layout.new_widget("item_1");
+    let mut item_2 = layout.new_widget("item_2");
+    let mut label = layout.new_widget("label");
+
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(300.0, 100.0))
+    ]);
+    let mut settings = LinearLayoutSettings::new(Orientation::Horizontal);
+    settings.item_align = ItemAlignment::Top;
+    let linear_layout = LinearLayout::new(&mut *root, settings);
+    root.set_container(linear_layout);
+
+    item_1.add(size(Size::new(50.0, 50.0)));
+    item_2.add(size(Size::new(100.0, 50.0)));
+    label.add(constraints![
+        size(Size::new(20.0, 20.0)),
+        align_start(&*item_2).padding(10.0),
+        align_top(&*item_2),
+    ]);
+
+    root.add_child(item_1.deref_mut());
+    root.add_child(item_2.deref_mut());
+    item_2.add_child(label.deref_mut());
+
+    let widgets = vec![root.clone(), item_1.clone(), item_2.clone(), label.clone()];
+    let set_direction = |direction| {
+        for widget in &widgets {
+            widget.0.borrow_mut().set_direction(direction);
+        }
+    };
+    set_direction(Direction::RightToLeft);
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.layout_rects[&item_1.id] == Rect::new(Point::new(250.0, 0.0), Size::new(50.0, 50.0)));
+    assert!(layout.layout_rects[&item_2.id] == Rect::new(Point::new(150.0, 0.0), Size::new(100.0, 50.0)));
+    assert!(layout.layout_rects[&label.id] == Rect::new(Point::new(220.0, 0.0), Size::new(20.0, 20.0)));
+
+    set_direction(Direction::LeftToRight);
+    layout.update();
+    assert!(layout.layout_rects[&item_1.id] == Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 50.0)));
+    assert!(layout.layout_rects[&item_2.id] == Rect::new(Point::new(50.0, 0.0), Size::new(100.0, 50.0)));
+    assert!(layout.layout_rects[&label.id] == Rect::new(Point::new(60.0, 0.0), Size::new(20.0, 20.0)));
+}
+
+#[test]
+fn direction_toggle() {
+    use layout::Direction;
+
+    let mut layout = TestLayout::new();
+    layout.solver.strict = false;
+
+    let mut root = layout.new_widget("root");
+    let mut item = layout.new_widget("item");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(300.0, 100.0)),
+    ]);
+    item.add(constraints![
+        align_start(&*root).padding(10.0),
+        align_top(&*root),
+        size(Size::new(50.0, 50.0)),
+    ]);
+    root.add_child(item.deref_mut());
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.layout_rects[&item.id] == Rect::new(Point::new(10.0, 0.0), Size::new(50.0, 50.0)));
+
+    let widgets = vec![root.clone(), item.clone()];
+    let set_direction = |direction| {
+        for widget in &widgets {
+            widget.0.borrow_mut().set_direction(direction);
+        }
+    };
+    set_direction(Direction::RightToLeft);
+    layout.update();
+    assert!(layout.layout_rects[&item.id] == Rect::new(Point::new(240.0, 0.0), Size::new(50.0, 50.0)));
+    assert!(layout.solver.take_errors().is_empty());
+
+    // the start edge stays bound to the right edge, so pinning the left edge is an error
+    item.add(align_left(&*root));
+    layout.update();
+    let errors = layout.solver.take_errors();
+    assert!(errors.len() == 1);
+    assert!(errors[0].kind == LayoutErrorKind::Unsatisfiable);
+    assert!(layout.layout_rects[&item.id] == Rect::new(Point::new(240.0, 0.0), Size::new(50.0, 50.0)));
+    item.remove_constraint(errors[0].constraint.clone());
+
+    set_direction(Direction::LeftToRight);
+    layout.update();
+    assert!(layout.layout_rects[&item.id] == Rect::new(Point::new(10.0, 0.0), Size::new(50.0, 50.0)));
+    assert!(layout.solver.take_errors().is_empty());
+}
+
 #[test]
 fn linear_layout_end_padding() {
     use layout::linear_layout::{LinearLayout, LinearLayoutSettings, Orientation, ItemAlignment, Spacing};
@@ -381,6 +530,507 @@ fn linear_layout_remove() {
     }));
 }
 
+#[test]
+fn flow_layout() {
+    use layout::flow_layout::{FlowLayout, FlowLayoutSettings};
+
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(100.0, 100.0)),
+    ]);
+    let mut settings = FlowLayoutSettings::new();
+    settings.horizontal_gap = 10.0;
+    settings.vertical_gap = 5.0;
+    root.set_container(FlowLayout::new(settings));
+
+    let widgets = {
+        let mut widgets = Vec::new();
+        for i in 0..3 {
+            let mut widget = layout.new_widget(&format!("widget_{}", i));
+            widget.add(size(Size::new(40.0, 10.0)));
+            root.add_child(widget.deref_mut());
+            widgets.push(widget);
+        }
+        widgets
+    };
+
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.layout_changed());
+    layout.update();
+    assert!(!layout.layout_changed());
+
+    assert!(layout.match_layouts(hashmap!{
+        root.id => Rect::new(Point::new(0.0, 0.0), Size::new(100.0, 100.0)),
+        widgets[0].id => Rect::new(Point::new(0.0, 0.0), Size::new(40.0, 10.0)),
+        widgets[1].id => Rect::new(Point::new(50.0, 0.0), Size::new(40.0, 10.0)),
+        widgets[2].id => Rect::new(Point::new(0.0, 15.0), Size::new(40.0, 10.0)),
+    }));
+}
+
+#[test]
+fn flow_layout_center() {
+    use layout::flow_layout::{FlowLayout, FlowLayoutSettings, RowAlignment};
+
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(100.0, 100.0)),
+    ]);
+    let mut settings = FlowLayoutSettings::new();
+    settings.horizontal_gap = 10.0;
+    settings.row_align = RowAlignment::Center;
+    root.set_container(FlowLayout::new(settings));
+
+    let widgets = {
+        let mut widgets = Vec::new();
+        for i in 0..3 {
+            let mut widget = layout.new_widget(&format!("widget_{}", i));
+            widget.add(size(Size::new(40.0, 10.0)));
+            root.add_child(widget.deref_mut());
+            widgets.push(widget);
+        }
+        widgets
+    };
+
+    layout.add_root(root.clone());
+    layout.update();
+    layout.layout_changed();
+    layout.update();
+
+    assert!(layout.match_layouts(hashmap!{
+        widgets[0].id => Rect::new(Point::new(5.0, 0.0), Size::new(40.0, 10.0)),
+        widgets[1].id => Rect::new(Point::new(55.0, 0.0), Size::new(40.0, 10.0)),
+        widgets[2].id => Rect::new(Point::new(30.0, 10.0), Size::new(40.0, 10.0)),
+    }));
+}
+
+#[test]
+fn unsatisfiable_error() {
+    let mut layout = TestLayout::new();
+    layout.solver.strict = false;
+
+    let mut widget = layout.new_widget("widget");
+    widget.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        width(100.0),
+    ]);
+    layout.add_root(widget.clone());
+    layout.update();
+    assert!(layout.solver.take_errors().is_empty());
+
+    widget.add(width(200.0));
+    layout.update();
+    let errors = layout.solver.take_errors();
+    assert!(errors.len() == 1);
+    let error = &errors[0];
+    assert!(error.layouts == vec!["widget".to_owned()]);
+    assert!(error.conflicting_desc.contains(&"REQD  widget.width == 100".to_owned()));
+    assert!(!layout.solver.has_constraint(&error.constraint));
+    assert!(layout.layout_rects[&widget.id].size.width == 100.0);
+}
+
+#[test]
+fn dot_graph() {
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut child = layout.new_widget("child");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(100.0, 100.0)),
+    ]);
+    child.add(align_left(&root).padding(10.0));
+    root.add_child(&mut *child);
+    layout.add_root(root.clone());
+    layout.update();
+
+    let graph = layout.solver.dot_graph();
+    assert!(graph.starts_with("digraph layout {"));
+    assert!(graph.contains("label=\"root\";"));
+    assert!(graph.contains("label=\"child\";"));
+    assert!(graph.contains("[label=\"width = 100\"]"));
+    assert!(graph.contains("tooltip=\"REQD  child.left == root.left + 10\""));
+}
+
+#[test]
+fn isolated_layout() {
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut panel = layout.new_widget("panel");
+    let mut item = layout.new_widget("item");
+    root.add(top_left(Point::new(0.0, 0.0)));
+    root.edit_right().set(200.0).strength(STRONG);
+    root.edit_bottom().set(200.0).strength(STRONG);
+    panel.isolate();
+    panel.add(constraints![
+        align_right(&root),
+        align_bottom(&root),
+        size(Size::new(100.0, 100.0)).strength(MEDIUM),
+    ]);
+    // would make the panel wider, if it weren't isolated
+    item.add(min_width(150.0).strength(STRONG));
+    root.add_child(&mut *panel);
+    panel.add_child(&mut *item);
+
+    // item is added to the solver before the panel, and moved to the panel's island later
+    layout.solver.update_layout(&mut *item);
+    layout.add_root(root.clone());
+    layout.update();
+
+    assert!(layout.match_layouts(hashmap!{
+        root.id => Rect::new(Point::new(0.0, 0.0), Size::new(200.0, 200.0)),
+        panel.id => Rect::new(Point::new(100.0, 100.0), Size::new(100.0, 100.0)),
+        item.id => Rect::new(Point::new(100.0, 100.0), Size::new(100.0, 100.0)),
+    }));
+
+    root.edit_right().set(300.0).strength(STRONG);
+    root.edit_bottom().set(300.0).strength(STRONG);
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(200.0, 200.0), Size::new(100.0, 100.0)),
+        item.id => Rect::new(Point::new(200.0, 200.0), Size::new(100.0, 100.0)),
+    }));
+}
+
+#[test]
+fn isolated_layout_outside_reference() {
+    let mut layout = TestLayout::new();
+    layout.solver.strict = false;
+
+    let mut root = layout.new_widget("root");
+    let mut panel = layout.new_widget("panel");
+    let mut item = layout.new_widget("item");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(200.0, 200.0)),
+    ]);
+    panel.isolate();
+    panel.add(constraints![
+        align_right(&root),
+        align_bottom(&root),
+        size(Size::new(100.0, 100.0)),
+    ]);
+    item.add(constraints![
+        align_top(&panel),
+        align_left(&panel),
+        size(Size::new(50.0, 50.0)),
+    ]);
+    root.add_child(&mut *panel);
+    panel.add_child(&mut *item);
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.solver.take_errors().is_empty());
+
+    // the root isn't part of the panel's island, so this can't be solved there
+    item.add(align_bottom(&root));
+    layout.update();
+    let errors = layout.solver.take_errors();
+    assert!(errors.len() == 1);
+    assert!(errors[0].kind == LayoutErrorKind::CrossesIsolation);
+    assert!(!layout.solver.has_constraint(&errors[0].constraint));
+    assert!(layout.match_layouts(hashmap!{
+        item.id => Rect::new(Point::new(100.0, 100.0), Size::new(50.0, 50.0)),
+    }));
+}
+
+#[test]
+fn isolated_layout_update_solver() {
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut panel = layout.new_widget("panel");
+    let mut item = layout.new_widget("item");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(200.0, 200.0)),
+    ]);
+    panel.isolate();
+    panel.add(constraints![
+        align_right(&root),
+        align_bottom(&root),
+        size(Size::new(100.0, 100.0)),
+    ]);
+    item.add(constraints![
+        bound_by(&panel),
+        align_top(&panel),
+        size(Size::new(50.0, 50.0)),
+    ]);
+    root.add_child(&mut *panel);
+    panel.add_child(&mut *item);
+    layout.add_root(root.clone());
+    layout.update();
+
+    // the item's variables are solved in the panel's island, not the main solver
+    let item_left = item.layout_ref().left;
+    layout.solver.update_solver(item.id, |solver| {
+        solver.add_edit_variable(item_left, STRONG).unwrap();
+        solver.suggest_value(item_left, 130.0).unwrap();
+    });
+    assert!(!layout.solver.solver.has_edit_variable(&item_left));
+    assert!(layout.solver.has_edit_variable(&item_left));
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        item.id => Rect::new(Point::new(130.0, 100.0), Size::new(50.0, 50.0)),
+    }));
+}
+
+#[test]
+fn fraction_of() {
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut sidebar = layout.new_widget("sidebar");
+    let mut content = layout.new_widget("content");
+    let mut footer = layout.new_widget("footer");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(200.0, 100.0)),
+    ]);
+    sidebar.add(constraints![
+        align_left(&root),
+        align_top(&root),
+        width_fraction_of(&root, 0.4),
+        height_fraction_of(&root, 0.5),
+    ]);
+    content.add(constraints![
+        to_right_of(&sidebar),
+        align_top(&root),
+        align_right(&root),
+        match_height(&sidebar).multiplier(0.5).offset(10.0),
+    ]);
+    footer.add(constraints![
+        align_left(&root),
+        align_bottom(&root),
+        match_width(&root).multiplier(0.5).padding(20.0),
+        match_height(&root).multiplier(0.1),
+    ]);
+    root.add_child(&mut *sidebar);
+    root.add_child(&mut *content);
+    root.add_child(&mut *footer);
+    layout.add_root(root.clone());
+    layout.update();
+
+    assert!(layout.match_layouts(hashmap!{
+        sidebar.id => Rect::new(Point::new(0.0, 0.0), Size::new(80.0, 50.0)),
+        content.id => Rect::new(Point::new(80.0, 0.0), Size::new(120.0, 35.0)),
+        footer.id => Rect::new(Point::new(0.0, 90.0), Size::new(80.0, 10.0)),
+    }));
+}
+
+#[test]
+fn layout_macro() {
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut left = layout.new_widget("left");
+    let mut right = layout.new_widget("right");
+    let padding = 5.0;
+    root.add(constraints![
+        layout!(LAYOUT.left == 0),
+        layout!(LAYOUT.top == 0),
+        layout!(LAYOUT.width == 200),
+        layout!(LAYOUT.height == 100),
+    ]);
+    left.add(constraints![
+        layout!(LAYOUT.left == root.left + (padding)),
+        layout!(LAYOUT.top == root.top),
+        layout!(2 * LAYOUT.width == root.width - 10 @ STRONG),
+        layout!(LAYOUT.height <= root.height * 0.5),
+        layout!(LAYOUT.height >= 40),
+    ]);
+    right.add(constraints![
+        layout!(left.right + 8 == LAYOUT.left),
+        layout!(LAYOUT.right <= (root.clone()).right),
+        layout!(LAYOUT.top == left.bottom @ REQUIRED - 1.0),
+        layout!(LAYOUT.height == -(padding) + 20),
+    ]);
+    root.add_child(&mut *left);
+    root.add_child(&mut *right);
+    layout.add_root(root.clone());
+    layout.update();
+
+    assert!(layout.match_layouts(hashmap!{
+        root.id => Rect::new(Point::new(0.0, 0.0), Size::new(200.0, 100.0)),
+        left.id => Rect::new(Point::new(5.0, 0.0), Size::new(95.0, 50.0)),
+        right.id => Rect::new(Point::new(108.0, 50.0), Size::new(92.0, 15.0)),
+    }));
+}
+
+#[test]
+fn constraint_sets() {
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut panel = layout.new_widget("panel");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(200.0, 100.0)),
+    ]);
+    panel.add_set("docked", constraints![
+        align_left(&root),
+        align_top(&root),
+        width(50.0),
+        match_height(&root),
+    ]);
+    panel.add_set("floating", constraints![
+        top_left(Point::new(20.0, 20.0)),
+        size(Size::new(30.0, 30.0)),
+    ]);
+    panel.enable_set("docked");
+    root.add_child(&mut *panel);
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 100.0)),
+    }));
+
+    panel.swap_sets("docked", "floating");
+    assert!(!panel.is_set_enabled("docked") && panel.is_set_enabled("floating"));
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(20.0, 20.0), Size::new(30.0, 30.0)),
+    }));
+
+    // replacing an enabled set keeps it enabled
+    panel.add_set("floating", constraints![
+        top_left(Point::new(40.0, 40.0)),
+        size(Size::new(30.0, 30.0)),
+    ]);
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(40.0, 40.0), Size::new(30.0, 30.0)),
+    }));
+
+    panel.swap_sets("floating", "docked");
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 100.0)),
+    }));
+}
+
+#[test]
+fn constraint_sets_toggle() {
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut panel = layout.new_widget("panel");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(200.0, 100.0)),
+    ]);
+    panel.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(50.0, 50.0)).strength(MEDIUM),
+    ]);
+    panel.add_set("wide", width(150.0));
+    root.add_child(&mut *panel);
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 50.0)),
+    }));
+
+    // breakpoints can turn a set on and off again before the solver sees it
+    panel.enable_set("wide");
+    panel.disable_set("wide");
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 50.0)),
+    }));
+
+    panel.enable_set("wide");
+    panel.enable_set("wide");
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(0.0, 0.0), Size::new(150.0, 50.0)),
+    }));
+
+    panel.disable_set("wide");
+    panel.enable_set("wide");
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(0.0, 0.0), Size::new(150.0, 50.0)),
+    }));
+
+    panel.disable_set("wide");
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        panel.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 50.0)),
+    }));
+
+    // sets that don't exist yet are ignored
+    panel.enable_set("tall");
+    assert!(!panel.is_set_enabled("tall"));
+}
+
+#[test]
+fn intrinsic_size_replace() {
+    use cassowary::WeightedRelation::*;
+    use layout::linear_layout::{LinearLayout, LinearLayoutSettings, Orientation, ItemAlignment};
+
+    let mut layout = TestLayout::new();
+
+    let mut root = layout.new_widget("root");
+    let mut label = layout.new_widget("label");
+    let mut text = layout.new_widget("text");
+    root.add(constraints![
+        top_left(Point::new(0.0, 0.0)),
+        size(Size::new(300.0, 100.0)),
+    ]);
+    let mut settings = LinearLayoutSettings::new(Orientation::Horizontal);
+    settings.item_align = ItemAlignment::Baseline;
+    let linear_layout = LinearLayout::new(&mut *root, settings);
+    root.set_container(linear_layout);
+
+    let baseline = label.baseline();
+    label.add(constraints![
+        size(Size::new(50.0, 30.0)),
+        baseline | EQ(REQUIRED) | 24.0,
+    ]);
+    root.add_child(label.deref_mut());
+    root.add_child(text.deref_mut());
+
+    // the size and baseline measured from the content are replaced as a group when it changes
+    let measure = |text: &mut SharedLayout, content_size: Size, text_baseline: f64| {
+        let baseline = text.baseline();
+        let mut constraints = size(content_size).build(&text.vars);
+        constraints.push(baseline | EQ(REQUIRED) | text_baseline);
+        text.add(constraints.clone());
+        constraints
+    };
+    let constraints = measure(&mut text, Size::new(40.0, 20.0), 14.0);
+    layout.add_root(root.clone());
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        label.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 30.0)),
+        text.id => Rect::new(Point::new(50.0, 10.0), Size::new(40.0, 20.0)),
+    }));
+
+    text.remove_constraints(constraints);
+    let constraints = measure(&mut text, Size::new(80.0, 40.0), 34.0);
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        label.id => Rect::new(Point::new(0.0, 10.0), Size::new(50.0, 30.0)),
+        text.id => Rect::new(Point::new(50.0, 0.0), Size::new(80.0, 40.0)),
+    }));
+
+    // content without a baseline falls back to aligning the bottom edge
+    text.remove_constraints(constraints);
+    text.add(size(Size::new(80.0, 20.0)));
+    layout.update();
+    assert!(layout.match_layouts(hashmap!{
+        label.id => Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 30.0)),
+        text.id => Rect::new(Point::new(50.0, 4.0), Size::new(80.0, 20.0)),
+    }));
+}
+
 #[derive(Clone)]
 struct SharedLayout(Rc<RefCell<Layout>>);
 impl SharedLayout {
@@ -459,6 +1109,16 @@ impl TestLayout {
             }
         }
     }
+    // notifies containers of the solved bounds, returns true if any layouts need updating
+    fn layout_changed(&mut self) -> bool {
+        let layout_rects = &self.layout_rects;
+        let mut changed = false;
+        for layout in self.layouts.values() {
+            let mut layout = layout.clone();
+            changed |= layout.layout_changed(&|id| layout_rects.get(&id).cloned());
+        }
+        changed
+    }
     fn match_layouts(&self, layouts: HashMap<LayoutId, Rect>) -> bool {
         for (match_layout_id, match_layout_rect) in layouts {
             let layout_rect = self.layout_rects[&match_layout_id];
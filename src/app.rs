@@ -116,6 +116,8 @@ impl App {
         while let Some((event_address, type_id, data)) = event::queue_next() {
             self.ui.handle_event(event_address, type_id, data.as_ref());
         }
+        // the layout has settled, widgets can be measured again when their width next changes
+        self.ui.remeasured.clear();
     }
 
     /// Add a new global event handler
@@ -10,6 +10,13 @@ fn rgba(color: u32) -> ColorF {
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Color(u32);
 
+impl Color {
+    /// Create a color from a `0xRRGGBBAA` value
+    pub fn from_rgba(rgba: u32) -> Self {
+        Color(rgba)
+    }
+}
+
 impl ::std::fmt::Debug for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         write!(f, "Color(0x{:08X})", self.0)
@@ -0,0 +1,825 @@
+//! Builds widget trees from a JSON description, so layouts can be changed without recompiling.
+//!
+//! ```json
+//! {
+//!     "type": "widget",
+//!     "name": "root",
+//!     "container": { "linear_layout": { "orientation": "vertical", "item_align": "fill" } },
+//!     "children": [
+//!         { "type": "text", "name": "title", "text": "Hello", "style": { "font_size": 30 } },
+//!         { "type": "button", "name": "save", "text": "Save",
+//!           "constraints": [ { "align_right": { "of": "parent", "padding": 10 } } ] }
+//!     ]
+//! }
+//! ```
+//!
+//! Constraints refer to other widgets by name, or to the parent widget as `"parent"`, which
+//! can't be used as a widget name.
+//! Handlers are attached after loading, using the names to look up widgets:
+//!
+//! ```ignore
+//! let ui = description::load_file("ui.json")?;
+//! ui.widget("save").unwrap().add_handler_fn(|_: &ClickEvent, _| println!("saved"));
+//! root.add_child(ui.root);
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use cassowary::strength::*;
+use serde_json;
+
+use text_layout::{Align, Wrap, Overflow};
+
+use widget::{WidgetBuilder, WidgetRef};
+use widget::style::Value;
+use widgets::text::TextBuilder;
+use widgets::button::{PushButtonBuilder, ToggleButtonBuilder};
+use widgets::edit_text::EditTextBuilder;
+use widgets::list::ListBuilder;
+use widgets::slider::SliderBuilder;
+use widgets::scroll::ScrollBuilder;
+use draw::rect::{RectState, RectStyle};
+use draw::text::TextStyle;
+use resources::font::{FontWeight, FontStyle};
+use layout::LayoutVars;
+use layout::constraint::*;
+use layout::linear_layout::{LinearLayoutSettings, Orientation, Spacing, ItemAlignment};
+use layout::flow_layout::{FlowLayoutSettings, RowAlignment};
+use geometry::{Point, Size};
+use color::Color;
+
+/// The widgets built from a description, see the module documentation
+pub struct LoadedUi {
+    pub root: WidgetBuilder,
+    widgets: HashMap<String, WidgetRef>,
+}
+
+impl LoadedUi {
+    /// Look up a named widget, to attach handlers to it
+    pub fn widget(&self, name: &str) -> Option<WidgetRef> {
+        self.widgets.get(name).cloned()
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    /// A constraint refers to a widget name that isn't in the description
+    UnknownWidget(String),
+    /// More than one widget has the same name
+    DuplicateName(String),
+    /// `"parent"` refers to the parent widget in constraints, so it can't name a widget
+    ReservedName(String),
+    /// Color strings are `#RRGGBB` or `#RRGGBBAA`
+    InvalidColor(String),
+    /// Scroll widgets need exactly one child, their content
+    ScrollContent(usize),
+    /// Flow layout rows have no spacing to stretch, so they can't be justified
+    JustifiedFlowLayout,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io(ref err) => write!(f, "Failed to read UI description: {}", err),
+            LoadError::Parse(ref err) => write!(f, "Failed to parse UI description: {}", err),
+            LoadError::UnknownWidget(ref name) => write!(f, "No widget named {:?}", name),
+            LoadError::DuplicateName(ref name) => write!(f, "More than one widget named {:?}", name),
+            LoadError::ReservedName(ref name) => write!(f, "Widgets can't be named {:?}", name),
+            LoadError::InvalidColor(ref color) => write!(f, "Invalid color {:?}", color),
+            LoadError::ScrollContent(count) => write!(f, "Scroll widget needs one child, found {}", count),
+            LoadError::JustifiedFlowLayout => write!(f, "Flow layout rows can't be justified"),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn description(&self) -> &str {
+        "failed to load UI description"
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        LoadError::Parse(err)
+    }
+}
+
+pub fn load_file<P: AsRef<Path>>(path: P) -> Result<LoadedUi, LoadError> {
+    let mut json = String::new();
+    File::open(path)?.read_to_string(&mut json)?;
+    load_str(&json)
+}
+
+pub fn load_str(json: &str) -> Result<LoadedUi, LoadError> {
+    let desc: WidgetDesc = serde_json::from_str(json)?;
+    load(&desc)
+}
+
+pub fn load(desc: &WidgetDesc) -> Result<LoadedUi, LoadError> {
+    let mut widgets = HashMap::new();
+    // widgets are created before any constraints are added, so constraints can refer to
+    // widgets that are described later
+    let node = create(desc, &mut widgets)?;
+    let root = assemble(node, None, &widgets)?;
+    Ok(LoadedUi {
+        root: root,
+        widgets: widgets,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WidgetDesc {
+    #[serde(rename = "type")]
+    pub widget_type: WidgetType,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Text for `text` and `button` widgets
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Text for `toggle_button` widgets
+    #[serde(default)]
+    pub on_text: Option<String>,
+    #[serde(default)]
+    pub off_text: Option<String>,
+    /// For `slider` widgets
+    #[serde(default)]
+    pub vertical: bool,
+    #[serde(default)]
+    pub range: Option<(f32, f32)>,
+    #[serde(default)]
+    pub value: Option<f32>,
+    /// For `scroll` widgets
+    #[serde(default)]
+    pub scrollbars: bool,
+    #[serde(default)]
+    pub style: StyleDesc,
+    #[serde(default)]
+    pub container: Option<ContainerDesc>,
+    #[serde(default)]
+    pub constraints: Vec<ConstraintDesc>,
+    #[serde(default)]
+    pub children: Vec<WidgetDesc>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetType {
+    /// A plain widget, drawn as a rectangle if it has a style
+    Widget,
+    Text,
+    Button,
+    ToggleButton,
+    EditText,
+    List,
+    Slider,
+    Scroll,
+}
+
+/// Style of `widget` and `text` widgets. Colors are `#RRGGBB` or `#RRGGBBAA` strings.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StyleDesc {
+    pub background_color: Option<String>,
+    pub corner_radius: Option<f32>,
+    pub border: Option<(f32, String)>,
+    pub text_color: Option<String>,
+    /// A font family, or the name of a font file under `assets/fonts`
+    pub font: Option<String>,
+    /// Weight from 100 to 900, 400 is normal and 700 bold
+    pub font_weight: Option<u16>,
+    pub font_style: Option<FontStyleDesc>,
+    pub font_size: Option<f32>,
+    pub align: Option<AlignDesc>,
+    pub wrap: Option<WrapDesc>,
+    pub overflow: Option<OverflowDesc>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlignDesc {
+    Start,
+    Middle,
+    End,
+    Justify,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WrapDesc {
+    NoWrap,
+    Character,
+    Whitespace,
+    Unicode,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowDesc {
+    Clip,
+    EllipsisEnd,
+    EllipsisMiddle,
+    MaxLines(usize),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FontStyleDesc {
+    Normal,
+    Italic,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerDesc {
+    LinearLayout(LinearLayoutDesc),
+    Grid { columns: usize },
+    FlowLayout(FlowLayoutDesc),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LinearLayoutDesc {
+    pub orientation: OrientationDesc,
+    #[serde(default)]
+    pub spacing: Option<SpacingDesc>,
+    #[serde(default)]
+    pub item_align: Option<ItemAlignDesc>,
+    #[serde(default)]
+    pub fill_equal: bool,
+    #[serde(default)]
+    pub padding: f32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrientationDesc {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpacingDesc {
+    Around,
+    Between,
+    End,
+    Start,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemAlignDesc {
+    None,
+    Fill,
+    Center,
+    Left,
+    Right,
+    Start,
+    End,
+    Top,
+    Bottom,
+    Baseline,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct FlowLayoutDesc {
+    pub horizontal_gap: f32,
+    pub vertical_gap: f32,
+    pub row_align: Option<AlignDesc>,
+}
+
+/// A constraint on the size or position of a widget, eg. `{ "width": 100 }`,
+/// `"shrink"` or `{ "align_left": { "of": "parent", "padding": 10 } }`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConstraintDesc {
+    Width(f32),
+    Height(f32),
+    MinWidth(f32),
+    MinHeight(f32),
+    Size((f32, f32)),
+    MinSize((f32, f32)),
+    AspectRatio(f32),
+    TopLeft((f32, f32)),
+    Shrink,
+    ShrinkHorizontal,
+    ShrinkVertical,
+    Center(String),
+    CenterHorizontal(String),
+    CenterVertical(String),
+    AlignTop(RelativeDesc),
+    AlignBottom(RelativeDesc),
+    AlignLeft(RelativeDesc),
+    AlignRight(RelativeDesc),
+    AlignStart(RelativeDesc),
+    AlignEnd(RelativeDesc),
+    AlignAbove(RelativeDesc),
+    AlignBelow(RelativeDesc),
+    AlignToLeftOf(RelativeDesc),
+    AlignToRightOf(RelativeDesc),
+    Above(RelativeDesc),
+    Below(RelativeDesc),
+    ToLeftOf(RelativeDesc),
+    ToRightOf(RelativeDesc),
+    ToStartOf(RelativeDesc),
+    ToEndOf(RelativeDesc),
+    BoundLeft(RelativeDesc),
+    BoundTop(RelativeDesc),
+    BoundRight(RelativeDesc),
+    BoundBottom(RelativeDesc),
+    BoundBy(RelativeDesc),
+    MatchLayout(RelativeDesc),
+    MatchWidth(RelativeDesc),
+    MatchHeight(RelativeDesc),
+}
+
+/// The widget a constraint is relative to, either just its name or the name with options
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RelativeDesc {
+    Name(String),
+    Options {
+        of: String,
+        #[serde(default)]
+        padding: f32,
+        #[serde(default)]
+        strength: Option<StrengthDesc>,
+        /// Only used by `match_width` and `match_height`
+        #[serde(default)]
+        multiplier: Option<f32>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StrengthDesc {
+    Required,
+    Strong,
+    Medium,
+    Weak,
+}
+
+impl StrengthDesc {
+    fn strength(&self) -> f64 {
+        match *self {
+            StrengthDesc::Required => REQUIRED,
+            StrengthDesc::Strong => STRONG,
+            StrengthDesc::Medium => MEDIUM,
+            StrengthDesc::Weak => WEAK,
+        }
+    }
+}
+
+enum NodeBuilder {
+    Widget(WidgetBuilder),
+    // the content of a scroll widget is added differently from other children
+    Scroll(ScrollBuilder),
+}
+
+impl NodeBuilder {
+    fn widget(&mut self) -> &mut WidgetBuilder {
+        match *self {
+            NodeBuilder::Widget(ref mut widget) => widget,
+            NodeBuilder::Scroll(ref mut scroll) => &mut **scroll,
+        }
+    }
+    fn finish(self, children: Vec<WidgetBuilder>) -> Result<WidgetBuilder, LoadError> {
+        match self {
+            NodeBuilder::Widget(mut widget) => {
+                for child in children {
+                    widget.add_child(child);
+                }
+                Ok(widget)
+            }
+            NodeBuilder::Scroll(mut scroll) => {
+                if children.len() != 1 {
+                    return Err(LoadError::ScrollContent(children.len()));
+                }
+                scroll.add_content(children.into_iter().next().unwrap());
+                Ok(scroll.into())
+            }
+        }
+    }
+}
+
+struct Node<'a> {
+    desc: &'a WidgetDesc,
+    builder: NodeBuilder,
+    children: Vec<Node<'a>>,
+}
+
+fn create<'a>(desc: &'a WidgetDesc, widgets: &mut HashMap<String, WidgetRef>) -> Result<Node<'a>, LoadError> {
+    let mut builder = match desc.widget_type {
+        WidgetType::Widget => {
+            let mut widget = WidgetBuilder::new("widget");
+            let style = desc.style.rect_style()?;
+            if !style.is_empty() {
+                widget.set_draw_state_with_style(RectState::new(), style);
+            }
+            NodeBuilder::Widget(widget)
+        }
+        WidgetType::Text => {
+            let text = desc.text.clone().unwrap_or_default();
+            NodeBuilder::Widget(TextBuilder::new_with_style(desc.style.text_style(text)?))
+        }
+        WidgetType::Button => {
+            let mut button = PushButtonBuilder::new();
+            if let Some(ref text) = desc.text {
+                button.set_text(text);
+            }
+            NodeBuilder::Widget(button.into())
+        }
+        WidgetType::ToggleButton => {
+            let mut button = ToggleButtonBuilder::new();
+            if let (&Some(ref on_text), &Some(ref off_text)) = (&desc.on_text, &desc.off_text) {
+                button.set_text(on_text, off_text);
+            }
+            NodeBuilder::Widget(button.into())
+        }
+        WidgetType::EditText => NodeBuilder::Widget(EditTextBuilder::new().into()),
+        WidgetType::List => NodeBuilder::Widget(ListBuilder::new().into()),
+        WidgetType::Slider => {
+            let mut slider = SliderBuilder::new();
+            if desc.vertical {
+                slider.make_vertical();
+            }
+            if let Some((start, end)) = desc.range {
+                slider.set_range(start..end);
+            }
+            if let Some(value) = desc.value {
+                slider.set_value(value);
+            }
+            NodeBuilder::Widget(slider.into())
+        }
+        WidgetType::Scroll => {
+            let mut scroll = ScrollBuilder::new();
+            if desc.scrollbars {
+                scroll.add_scrollbar();
+            }
+            NodeBuilder::Scroll(scroll)
+        }
+    };
+    if let Some(ref name) = desc.name {
+        if name == "parent" {
+            return Err(LoadError::ReservedName(name.clone()));
+        }
+        builder.widget().set_name(name);
+        if widgets.insert(name.clone(), builder.widget().widget_ref()).is_some() {
+            return Err(LoadError::DuplicateName(name.clone()));
+        }
+    }
+    let mut children = Vec::new();
+    for child in &desc.children {
+        children.push(create(child, widgets)?);
+    }
+    Ok(Node {
+        desc: desc,
+        builder: builder,
+        children: children,
+    })
+}
+
+fn assemble(node: Node, parent: Option<&LayoutVars>, widgets: &HashMap<String, WidgetRef>) -> Result<WidgetBuilder, LoadError> {
+    let Node { desc, mut builder, children } = node;
+    if let Some(ref container) = desc.container {
+        container.apply(builder.widget())?;
+    }
+    let resolver = Resolver { parent: parent, widgets: widgets };
+    for constraint in &desc.constraints {
+        let constraint = constraint.builder(&resolver)?;
+        builder.widget().layout().add(constraint);
+    }
+    let vars = builder.widget().widget_ref().layout_vars();
+    let mut child_builders = Vec::new();
+    for child in children {
+        child_builders.push(assemble(child, Some(&vars), widgets)?);
+    }
+    builder.finish(child_builders)
+}
+
+struct Resolver<'a> {
+    parent: Option<&'a LayoutVars>,
+    widgets: &'a HashMap<String, WidgetRef>,
+}
+
+impl<'a> Resolver<'a> {
+    fn resolve(&self, name: &str) -> Result<LayoutVars, LoadError> {
+        let vars = if name == "parent" {
+            self.parent.cloned()
+        } else {
+            self.widgets.get(name).map(|widget| widget.layout_vars())
+        };
+        vars.ok_or_else(|| LoadError::UnknownWidget(name.to_owned()))
+    }
+}
+
+impl RelativeDesc {
+    fn name(&self) -> &str {
+        match *self {
+            RelativeDesc::Name(ref name) => name,
+            RelativeDesc::Options { ref of, .. } => of,
+        }
+    }
+    fn paddable<F>(&self, resolver: &Resolver, build: F) -> Result<Box<ConstraintBuilder>, LoadError>
+        where F: Fn(&LayoutVars) -> PaddableConstraintBuilder
+    {
+        let mut builder = build(&resolver.resolve(self.name())?);
+        if let RelativeDesc::Options { padding, strength, .. } = *self {
+            builder = builder.padding(padding);
+            if let Some(strength) = strength {
+                builder = builder.strength(strength.strength());
+            }
+        }
+        Ok(Box::new(builder))
+    }
+    fn scalable<F>(&self, resolver: &Resolver, build: F) -> Result<Box<ConstraintBuilder>, LoadError>
+        where F: Fn(&LayoutVars) -> ScalableConstraintBuilder
+    {
+        let mut builder = build(&resolver.resolve(self.name())?);
+        if let RelativeDesc::Options { padding, strength, multiplier, .. } = *self {
+            builder = builder.padding(padding);
+            if let Some(strength) = strength {
+                builder = builder.strength(strength.strength());
+            }
+            if let Some(multiplier) = multiplier {
+                builder = builder.multiplier(multiplier);
+            }
+        }
+        Ok(Box::new(builder))
+    }
+}
+
+impl ConstraintDesc {
+    fn builder(&self, resolver: &Resolver) -> Result<Box<ConstraintBuilder>, LoadError> {
+        Ok(match *self {
+            ConstraintDesc::Width(val) => Box::new(width(val)),
+            ConstraintDesc::Height(val) => Box::new(height(val)),
+            ConstraintDesc::MinWidth(val) => Box::new(min_width(val)),
+            ConstraintDesc::MinHeight(val) => Box::new(min_height(val)),
+            ConstraintDesc::Size((w, h)) => Box::new(size(Size::new(w, h))),
+            ConstraintDesc::MinSize((w, h)) => Box::new(min_size(Size::new(w, h))),
+            ConstraintDesc::AspectRatio(val) => Box::new(aspect_ratio(val)),
+            ConstraintDesc::TopLeft((x, y)) => Box::new(top_left(Point::new(x, y))),
+            ConstraintDesc::Shrink => Box::new(shrink()),
+            ConstraintDesc::ShrinkHorizontal => Box::new(shrink_horizontal()),
+            ConstraintDesc::ShrinkVertical => Box::new(shrink_vertical()),
+            ConstraintDesc::Center(ref name) => Box::new(center(&resolver.resolve(name)?)),
+            ConstraintDesc::CenterHorizontal(ref name) => Box::new(center_horizontal(&resolver.resolve(name)?)),
+            ConstraintDesc::CenterVertical(ref name) => Box::new(center_vertical(&resolver.resolve(name)?)),
+            ConstraintDesc::AlignTop(ref rel) => rel.paddable(resolver, align_top)?,
+            ConstraintDesc::AlignBottom(ref rel) => rel.paddable(resolver, align_bottom)?,
+            ConstraintDesc::AlignLeft(ref rel) => rel.paddable(resolver, align_left)?,
+            ConstraintDesc::AlignRight(ref rel) => rel.paddable(resolver, align_right)?,
+            ConstraintDesc::AlignStart(ref rel) => rel.paddable(resolver, align_start)?,
+            ConstraintDesc::AlignEnd(ref rel) => rel.paddable(resolver, align_end)?,
+            ConstraintDesc::AlignAbove(ref rel) => rel.paddable(resolver, align_above)?,
+            ConstraintDesc::AlignBelow(ref rel) => rel.paddable(resolver, align_below)?,
+            ConstraintDesc::AlignToLeftOf(ref rel) => rel.paddable(resolver, align_to_left_of)?,
+            ConstraintDesc::AlignToRightOf(ref rel) => rel.paddable(resolver, align_to_right_of)?,
+            ConstraintDesc::Above(ref rel) => rel.paddable(resolver, above)?,
+            ConstraintDesc::Below(ref rel) => rel.paddable(resolver, below)?,
+            ConstraintDesc::ToLeftOf(ref rel) => rel.paddable(resolver, to_left_of)?,
+            ConstraintDesc::ToRightOf(ref rel) => rel.paddable(resolver, to_right_of)?,
+            ConstraintDesc::ToStartOf(ref rel) => rel.paddable(resolver, to_start_of)?,
+            ConstraintDesc::ToEndOf(ref rel) => rel.paddable(resolver, to_end_of)?,
+            ConstraintDesc::BoundLeft(ref rel) => rel.paddable(resolver, bound_left)?,
+            ConstraintDesc::BoundTop(ref rel) => rel.paddable(resolver, bound_top)?,
+            ConstraintDesc::BoundRight(ref rel) => rel.paddable(resolver, bound_right)?,
+            ConstraintDesc::BoundBottom(ref rel) => rel.paddable(resolver, bound_bottom)?,
+            ConstraintDesc::BoundBy(ref rel) => rel.paddable(resolver, bound_by)?,
+            ConstraintDesc::MatchLayout(ref rel) => rel.paddable(resolver, match_layout)?,
+            ConstraintDesc::MatchWidth(ref rel) => rel.scalable(resolver, match_width)?,
+            ConstraintDesc::MatchHeight(ref rel) => rel.scalable(resolver, match_height)?,
+        })
+    }
+}
+
+impl ContainerDesc {
+    fn apply(&self, widget: &mut WidgetBuilder) -> Result<(), LoadError> {
+        match *self {
+            ContainerDesc::LinearLayout(ref desc) => {
+                let orientation = match desc.orientation {
+                    OrientationDesc::Horizontal => Orientation::Horizontal,
+                    OrientationDesc::Vertical => Orientation::Vertical,
+                };
+                let mut settings = LinearLayoutSettings::new(orientation);
+                if let Some(spacing) = desc.spacing {
+                    settings.spacing = match spacing {
+                        SpacingDesc::Around => Spacing::Around,
+                        SpacingDesc::Between => Spacing::Between,
+                        SpacingDesc::End => Spacing::End,
+                        SpacingDesc::Start => Spacing::Start,
+                    };
+                }
+                if let Some(item_align) = desc.item_align {
+                    settings.item_align = match item_align {
+                        ItemAlignDesc::None => ItemAlignment::None,
+                        ItemAlignDesc::Fill => ItemAlignment::Fill,
+                        ItemAlignDesc::Center => ItemAlignment::Center,
+                        ItemAlignDesc::Left => ItemAlignment::Left,
+                        ItemAlignDesc::Right => ItemAlignment::Right,
+                        ItemAlignDesc::Start => ItemAlignment::Start,
+                        ItemAlignDesc::End => ItemAlignment::End,
+                        ItemAlignDesc::Top => ItemAlignment::Top,
+                        ItemAlignDesc::Bottom => ItemAlignment::Bottom,
+                        ItemAlignDesc::Baseline => ItemAlignment::Baseline,
+                    };
+                }
+                settings.fill_equal = desc.fill_equal;
+                settings.padding = desc.padding;
+                widget.linear_layout(settings);
+            }
+            ContainerDesc::Grid { columns } => {
+                widget.grid(columns);
+            }
+            ContainerDesc::FlowLayout(ref desc) => {
+                let mut settings = FlowLayoutSettings::new();
+                settings.horizontal_gap = desc.horizontal_gap;
+                settings.vertical_gap = desc.vertical_gap;
+                if let Some(row_align) = desc.row_align {
+                    settings.row_align = match row_align {
+                        AlignDesc::Start => RowAlignment::Start,
+                        AlignDesc::Middle => RowAlignment::Center,
+                        AlignDesc::End => RowAlignment::End,
+                        AlignDesc::Justify => return Err(LoadError::JustifiedFlowLayout),
+                    };
+                }
+                widget.flow_layout(settings);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StyleDesc {
+    fn rect_style(&self) -> Result<Vec<RectStyle>, LoadError> {
+        let mut style = Vec::new();
+        if let Some(ref color) = self.background_color {
+            style.push(RectStyle::BackgroundColor(Value::from(parse_color(color)?)));
+        }
+        if let Some(corner_radius) = self.corner_radius {
+            style.push(RectStyle::CornerRadius(Value::from(Some(corner_radius))));
+        }
+        if let Some((border_width, ref color)) = self.border {
+            style.push(RectStyle::Border(Value::from(Some((border_width, parse_color(color)?)))));
+        }
+        Ok(style)
+    }
+    fn text_style(&self, text: String) -> Result<Vec<TextStyle>, LoadError> {
+        let mut style = vec![TextStyle::Text(Value::from(text))];
+        if let Some(ref color) = self.text_color {
+            style.push(TextStyle::TextColor(Value::from(parse_color(color)?)));
+        }
+        if let Some(ref color) = self.background_color {
+            style.push(TextStyle::BackgroundColor(Value::from(parse_color(color)?)));
+        }
+        if let Some(ref font) = self.font {
+            style.push(TextStyle::Font(Value::from(font.clone())));
+        }
+        if let Some(font_weight) = self.font_weight {
+            style.push(TextStyle::FontWeight(Value::from(FontWeight(font_weight))));
+        }
+        if let Some(font_style) = self.font_style {
+            let font_style = match font_style {
+                FontStyleDesc::Normal => FontStyle::Normal,
+                FontStyleDesc::Italic => FontStyle::Italic,
+            };
+            style.push(TextStyle::FontStyle(Value::from(font_style)));
+        }
+        if let Some(font_size) = self.font_size {
+            style.push(TextStyle::FontSize(Value::from(font_size)));
+        }
+        if let Some(align) = self.align {
+            let align = match align {
+                AlignDesc::Start => Align::Start,
+                AlignDesc::Middle => Align::Middle,
+                AlignDesc::End => Align::End,
+                AlignDesc::Justify => Align::Justify,
+            };
+            style.push(TextStyle::Align(Value::from(align)));
+        }
+        if let Some(wrap) = self.wrap {
+            let wrap = match wrap {
+                WrapDesc::NoWrap => Wrap::NoWrap,
+                WrapDesc::Character => Wrap::Character,
+                WrapDesc::Whitespace => Wrap::Whitespace,
+                WrapDesc::Unicode => Wrap::Unicode,
+            };
+            style.push(TextStyle::Wrap(Value::from(wrap)));
+        }
+        if let Some(overflow) = self.overflow {
+            let overflow = match overflow {
+                OverflowDesc::Clip => Overflow::Clip,
+                OverflowDesc::EllipsisEnd => Overflow::EllipsisEnd,
+                OverflowDesc::EllipsisMiddle => Overflow::EllipsisMiddle,
+                OverflowDesc::MaxLines(max_lines) => Overflow::MaxLines(max_lines),
+            };
+            style.push(TextStyle::Overflow(Value::from(overflow)));
+        }
+        Ok(style)
+    }
+}
+
+fn parse_color(color: &str) -> Result<Color, LoadError> {
+    let invalid = || LoadError::InvalidColor(color.to_owned());
+    if !color.starts_with('#') {
+        return Err(invalid());
+    }
+    let hex = &color[1..];
+    let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    match hex.len() {
+        6 => Ok(Color::from_rgba(value << 8 | 0xFF)),
+        8 => Ok(Color::from_rgba(value)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_str, LoadError};
+
+    fn load_error(json: &str) -> LoadError {
+        match load_str(json) {
+            Ok(_) => panic!("Loaded invalid description {}", json),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn minimal() {
+        let ui = load_str(r#"{ "type": "widget", "name": "root" }"#).unwrap();
+        assert!(ui.widget("root").is_some());
+        assert!(ui.widget("title").is_none());
+    }
+
+    #[test]
+    fn unknown_widget() {
+        let err = load_error(r#"{ "type": "widget", "children": [
+            { "type": "widget", "constraints": [ { "align_left": "title" } ] } ] }"#);
+        match err {
+            LoadError::UnknownWidget(ref name) if name == "title" => (),
+            _ => panic!("Unexpected error {}", err),
+        }
+        // the root has no parent to refer to
+        let err = load_error(r#"{ "type": "widget", "constraints": [ { "align_left": "parent" } ] }"#);
+        match err {
+            LoadError::UnknownWidget(ref name) if name == "parent" => (),
+            _ => panic!("Unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn unknown_constraint() {
+        let err = load_error(r#"{ "type": "widget", "constraints": [ { "align_middle": "parent" } ] }"#);
+        match err {
+            LoadError::Parse(_) => (),
+            _ => panic!("Unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let err = load_error(r#"{ "type": "widget", "constraints": [ { "width": "wide" } ] }"#);
+        match err {
+            LoadError::Parse(_) => (),
+            _ => panic!("Unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn invalid_names() {
+        let err = load_error(r#"{ "type": "widget", "name": "parent" }"#);
+        match err {
+            LoadError::ReservedName(ref name) if name == "parent" => (),
+            _ => panic!("Unexpected error {}", err),
+        }
+        let err = load_error(r#"{ "type": "widget", "name": "root", "children": [
+            { "type": "widget", "name": "root" } ] }"#);
+        match err {
+            LoadError::DuplicateName(ref name) if name == "root" => (),
+            _ => panic!("Unexpected error {}", err),
+        }
+    }
+
+    #[test]
+    fn justified_flow_layout() {
+        let err = load_error(r#"{ "type": "widget",
+            "container": { "flow_layout": { "row_align": "justify" } } }"#);
+        match err {
+            LoadError::JustifiedFlowLayout => (),
+            _ => panic!("Unexpected error {}", err),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use webrender::api::*;
 
 use render::RenderBuilder;
-use widget::draw::Draw;
+use widget::draw::{Draw, IntrinsicSize};
 use resources::resources;
 use geometry::{Rect, RectExt, Size, SizeExt};
 
@@ -37,4 +37,7 @@ impl Draw for ImageState {
             key,
         );
     }
+    fn intrinsic_size(&self) -> Option<IntrinsicSize> {
+        Some(IntrinsicSize::Exact(self.measure()))
+    }
 }
@@ -1,5 +1,6 @@
 pub mod rect;
 pub mod ellipse;
 pub mod text;
+pub mod rich_text;
 pub mod image;
 pub mod glcanvas;
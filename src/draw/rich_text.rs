@@ -0,0 +1,274 @@
+use std::ops::Range;
+
+use webrender::api::{LayoutPoint, GlyphInstance, PrimitiveInfo};
+
+use render::{self, RenderBuilder};
+use text_layout::{Wrap, Align};
+use text_layout::span::{self, Span, GlyphRun};
+use resources::resources;
+use resources::font::{FontDescriptor, FontWeight, FontStyle};
+use draw::text::font_spans;
+use geometry::{Size, Rect, RectExt, Point};
+use widget::draw::{Draw, IntrinsicSize};
+use color::*;
+
+/// The style of a run of text within a `RichTextState`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanStyle {
+    /// A font family registered in `Resources::font_registry`, or the name of a font file
+    /// under `assets/fonts`, see `TextState::font`
+    pub font: String,
+    pub font_weight: FontWeight,
+    pub font_style: FontStyle,
+    pub font_size: f32,
+    pub text_color: Color,
+    pub background_color: Color,
+    pub underline: bool,
+}
+
+impl SpanStyle {
+    pub fn font_descriptor(&self) -> FontDescriptor {
+        FontDescriptor::new(&self.font)
+            .weight(self.font_weight)
+            .style(self.font_style)
+    }
+}
+
+impl Default for SpanStyle {
+    fn default() -> Self {
+        SpanStyle {
+            font: "NotoSans".to_owned(),
+            font_weight: FontWeight::NORMAL,
+            font_style: FontStyle::Normal,
+            font_size: 24.0,
+            text_color: BLACK,
+            background_color: TRANSPARENT,
+            underline: false,
+        }
+    }
+}
+
+/// Changes to the base style of a `RichTextState` for a range of the text,
+/// any field left as `None` keeps the base style.
+#[derive(Debug, Clone, Default)]
+pub struct TextAttributes {
+    pub font: Option<String>,
+    pub font_weight: Option<FontWeight>,
+    pub font_style: Option<FontStyle>,
+    pub font_size: Option<f32>,
+    pub text_color: Option<Color>,
+    pub background_color: Option<Color>,
+    pub underline: Option<bool>,
+}
+
+impl TextAttributes {
+    fn apply(&self, style: &mut SpanStyle) {
+        if let Some(ref font) = self.font {
+            style.font = font.clone();
+        }
+        if let Some(font_weight) = self.font_weight {
+            style.font_weight = font_weight;
+        }
+        if let Some(font_style) = self.font_style {
+            style.font_style = font_style;
+        }
+        if let Some(font_size) = self.font_size {
+            style.font_size = font_size;
+        }
+        if let Some(text_color) = self.text_color {
+            style.text_color = text_color;
+        }
+        if let Some(background_color) = self.background_color {
+            style.background_color = background_color;
+        }
+        if let Some(underline) = self.underline {
+            style.underline = underline;
+        }
+    }
+}
+
+/// Attributes applied to a byte range of the text
+#[derive(Debug, Clone)]
+pub struct StyledRange {
+    pub range: Range<usize>,
+    pub attributes: TextAttributes,
+}
+
+/// Text made up of runs with different fonts, sizes and colors, laid out together
+/// so that mixed runs share lines, wrapping and alignment.
+pub struct RichTextState {
+    pub text: String,
+    /// The style of any text not covered by `ranges`
+    pub style: SpanStyle,
+    /// Applied in order on top of `style`, so later ranges take precedence where they overlap
+    pub ranges: Vec<StyledRange>,
+    pub wrap: Wrap,
+    pub align: Align,
+}
+
+impl Default for RichTextState {
+    fn default() -> Self {
+        RichTextState {
+            text: "".to_owned(),
+            style: SpanStyle::default(),
+            ranges: Vec::new(),
+            wrap: Wrap::Whitespace,
+            align: Align::Start,
+        }
+    }
+}
+
+impl RichTextState {
+    pub fn new() -> Self {
+        RichTextState::default()
+    }
+    /// Appends `text` with the given attributes
+    pub fn push(&mut self, text: &str, attributes: TextAttributes) -> &mut Self {
+        let start = self.text.len();
+        self.text.push_str(text);
+        self.ranges.push(StyledRange {
+            range: start..self.text.len(),
+            attributes: attributes,
+        });
+        self
+    }
+    /// Appends `text` in the base style
+    pub fn push_plain(&mut self, text: &str) -> &mut Self {
+        self.text.push_str(text);
+        self
+    }
+    /// Splits the text into runs that each have a single style, covering the whole text
+    pub fn styled_runs(&self) -> Vec<(Range<usize>, SpanStyle)> {
+        let len = self.text.len();
+        let mut bounds = vec![0, len];
+        for styled in &self.ranges {
+            bounds.push(styled.range.start.min(len));
+            bounds.push(styled.range.end.min(len));
+        }
+        bounds.sort();
+        bounds.dedup();
+
+        let mut runs: Vec<(Range<usize>, SpanStyle)> = Vec::new();
+        for window in bounds.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            let mut style = self.style.clone();
+            for styled in &self.ranges {
+                if styled.range.start <= start && end <= styled.range.end {
+                    styled.attributes.apply(&mut style);
+                }
+            }
+            match runs.last_mut() {
+                Some(&mut (ref mut range, ref last_style)) if *last_style == style => {
+                    range.end = end;
+                    continue;
+                }
+                _ => (),
+            }
+            runs.push((start..end, style));
+        }
+        if runs.is_empty() {
+            runs.push((0..0, self.style.clone()));
+        }
+        runs
+    }
+    /// Runs `f` with the text layout spans for each styled run, along with their styles.
+    /// Runs are split further where fallback fonts are needed, see `Resources::fallback_fonts`.
+    /// The `font` of the styles given to `f` is the name of the font file each span is drawn with.
+    fn with_spans<F, T>(&self, f: F) -> T
+        where F: FnOnce(&[Span], Vec<SpanStyle>) -> T
+    {
+        let runs = self.styled_runs();
+        let font_runs: Vec<(Range<usize>, String, f32)> = {
+            let resources = resources();
+            runs.iter()
+                .map(|&(ref range, ref style)| {
+                    (range.clone(), resources.resolve_font(&style.font_descriptor()), style.font_size)
+                })
+                .collect()
+        };
+        font_spans(&self.text, &font_runs, &[], |spans, span_fonts| {
+            let styles = span_fonts.iter().map(|&(run, ref font)| {
+                SpanStyle { font: font.clone(), ..runs[run].1.clone() }
+            }).collect();
+            f(spans, styles)
+        })
+    }
+    pub fn measure(&self) -> Size {
+        self.with_spans(|spans, _| span::get_text_size(&self.text, spans, self.wrap))
+    }
+    /// Height of the text once wrapped to `width`
+    pub fn measure_height(&self, width: f32) -> f32 {
+        self.with_spans(|spans, _| span::get_text_height(&self.text, spans, self.wrap, width))
+    }
+    /// Metrics of the first line, before wrapping
+    fn first_line_metrics(&self) -> span::LineMetrics {
+        let end = self.text.find('\n').unwrap_or(self.text.len());
+        self.with_spans(|spans, _| span::line_metrics(spans, 0..end))
+    }
+    fn glyph_runs(&self, bounds: Rect) -> (Vec<GlyphRun>, Vec<SpanStyle>) {
+        self.with_spans(|spans, styles| {
+            (span::get_glyph_runs(&self.text, spans, bounds, self.wrap, self.align), styles)
+        })
+    }
+    /// The byte index of the character at `point`, for text drawn in `bounds`
+    pub fn byte_index_at(&self, bounds: Rect, point: Point) -> Option<usize> {
+        let (runs, _) = self.glyph_runs(bounds);
+        span::byte_index_at(&runs, point)
+    }
+}
+
+impl Draw for RichTextState {
+    fn draw(&mut self, bounds: Rect, _: Rect, renderer: &mut RenderBuilder) {
+        let (runs, styles) = self.glyph_runs(bounds);
+        for run in &runs {
+            let background_color = styles[run.span].background_color;
+            if background_color != TRANSPARENT {
+                renderer.builder.push_rect(&PrimitiveInfo::new(run.rect.typed()), background_color.into());
+            }
+        }
+        // one glyph run per style, the glyphs of each span can be spread across several lines
+        let mut glyphs: Vec<Vec<GlyphInstance>> = styles.iter().map(|_| Vec::new()).collect();
+        for run in &runs {
+            glyphs[run.span].extend(run.glyphs.iter().map(|glyph| {
+                let position = glyph.position();
+                GlyphInstance {
+                    index: glyph.id().0,
+                    point: LayoutPoint::new(position.x, position.y),
+                }
+            }));
+        }
+        let info = PrimitiveInfo::new(bounds.typed());
+        for (style, glyphs) in styles.iter().zip(&glyphs) {
+            if glyphs.is_empty() {
+                continue;
+            }
+            let key = *resources().get_font_instance(&style.font, style.font_size);
+            renderer.builder.push_text(&info, glyphs, key, style.text_color.into(), None);
+        }
+        for run in &runs {
+            let style = &styles[run.span];
+            if style.underline {
+                let underline = run.baseline + (style.font_size / 12.0).round();
+                render::draw_horizontal_line(underline, run.rect.left(), run.rect.right(), style.text_color, renderer);
+            }
+        }
+    }
+    fn intrinsic_size(&self) -> Option<IntrinsicSize> {
+        let text_size = self.measure();
+        if self.wrap == Wrap::NoWrap {
+            Some(IntrinsicSize::Exact(text_size))
+        } else {
+            Some(IntrinsicSize::Wrapping { width: text_size.width, min_height: self.first_line_metrics().height })
+        }
+    }
+    fn baseline(&self) -> Option<f32> {
+        Some(self.first_line_metrics().ascent)
+    }
+    fn height_for_width(&self, width: f32) -> Option<f32> {
+        if self.wrap == Wrap::NoWrap {
+            None
+        } else {
+            Some(self.measure_height(width))
+        }
+    }
+}
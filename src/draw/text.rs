@@ -1,12 +1,22 @@
-use webrender::api::{LayoutPoint, GlyphInstance, PrimitiveInfo, FontInstanceKey};
+use std::cmp;
+use std::f32;
+use std::iter;
+use std::ops::Range;
+
+use webrender::api::{LayoutPoint, GlyphInstance, PrimitiveInfo};
 use rusttype::{Scale, GlyphId, VMetrics};
 
 use render::RenderBuilder;
-use text_layout::{self, Wrap, Align};
+use text_layout::{self, Wrap, Align, Overflow};
+use text_layout::span::{self, Span, GlyphRun};
+use text_layout::cursor::{self, Index};
+use text_layout::line::{self, LineInfo, LineInfos};
+use text_layout::bidi::BidiLine;
 use resources::resources;
-use geometry::{Size, Rect, RectExt, Vector};
+use resources::font::{FontDescriptor, FontWeight, FontStyle, FontStretch};
+use geometry::{Size, Rect, RectExt, Point, Vector};
 use render;
-use widget::draw::Draw;
+use widget::draw::{Draw, IntrinsicSize};
 use widget::property::PropSet;
 use widget::style::{self, Value, Style};
 use color::*;
@@ -15,23 +25,66 @@ const DEBUG_LINE_BOUNDS: bool = false;
 
 pub struct TextState {
     pub text: String,
+    /// A font family registered in `Resources::font_registry`, or the name of a font file
+    /// under `assets/fonts`, in which case the weight, style and stretch are ignored
     pub font: String,
+    pub font_weight: FontWeight,
+    pub font_style: FontStyle,
+    pub font_stretch: FontStretch,
+    /// Fonts tried in order for characters that `font` has no glyph for,
+    /// before the global `Resources::fallback_fonts`
+    pub fallback_fonts: Vec<String>,
     pub font_size: f32,
     pub text_color: Color,
     pub background_color: Color,
     pub wrap: Wrap,
     pub align: Align,
+    /// How text that doesn't fit in the bounds is shown, `text` is kept whole either way.
+    ///
+    /// Overflow only changes what is drawn and measured, see `display_text`. The cursor and
+    /// selection are byte indices into the whole `text`, and aren't drawn unless this is `Clip`.
+    pub overflow: Overflow,
+    /// Byte index of the text cursor, used when editing text
+    pub cursor: Option<usize>,
+    /// Byte index of the other end of the selection, which extends to `cursor`
+    pub selection_anchor: Option<usize>,
+    /// Whether the cursor and selection are drawn, such as while the text is focused
+    pub show_cursor: bool,
+}
+
+/// Ways of moving the text cursor, see `TextState::move_cursor`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CursorMovement {
+    /// To the previous grapheme cluster
+    Previous,
+    /// To the next grapheme cluster
+    Next,
+    /// To the start of the previous word
+    PreviousWord,
+    /// To the end of the next word
+    NextWord,
+    LineStart,
+    LineEnd,
 }
+
 impl Default for TextState {
     fn default() -> Self {
         TextState {
             text: "".to_owned(),
-            font: "NotoSans/NotoSans-Regular".to_owned(),
+            font: "NotoSans".to_owned(),
+            font_weight: FontWeight::NORMAL,
+            font_style: FontStyle::Normal,
+            font_stretch: FontStretch::Normal,
+            fallback_fonts: Vec::new(),
             font_size: 24.0,
             text_color: BLACK,
             background_color: TRANSPARENT,
             wrap: Wrap::Whitespace,
             align: Align::Start,
+            overflow: Overflow::Clip,
+            cursor: None,
+            selection_anchor: None,
+            show_cursor: false,
         }
     }
 }
@@ -43,41 +96,108 @@ impl TextState {
         draw_state
     }
     pub fn measure(&self) -> Size {
-        let line_height = self.line_height();
-        let mut resources = resources();
-        let font = resources.get_font(&self.font);
-        text_layout::get_text_size(
-            &self.text,
-            &font.info,
-            self.font_size,
-            line_height,
-            self.wrap)
+        let font_name = self.font_name();
+        self.with_spans(&font_name, &self.text, |spans, _| span::get_text_size(&self.text, spans, self.wrap))
+    }
+    /// Height of the text once wrapped to `width`, limited to the lines shown by `Overflow::MaxLines`
+    pub fn measure_height(&self, width: f32) -> f32 {
+        let font_name = self.font_name();
+        let text = self.overflow_text(&font_name, Size::new(width, f32::MAX));
+        self.with_spans(&font_name, &text, |spans, _| span::get_text_height(&text, spans, self.wrap, width))
+    }
+    /// The text as shown in `size`, with the `overflow` mode applied.
+    ///
+    /// Indices into the display text don't map back to `text`, where the ellipsis replaces part
+    /// of it, so cursor positions and `move_cursor` always use the whole `text`.
+    pub fn display_text(&self, size: Size) -> String {
+        self.overflow_text(&self.font_name(), size)
+    }
+    fn overflow_text(&self, font_name: &str, size: Size) -> String {
+        if self.overflow == Overflow::Clip {
+            return self.text.clone();
+        }
+        self.with_spans(font_name, &self.text, |spans, _| {
+            span::overflow_text(&self.text, spans, self.wrap, self.overflow, size)
+        })
+    }
+    pub fn font_descriptor(&self) -> FontDescriptor {
+        FontDescriptor::new(&self.font)
+            .weight(self.font_weight)
+            .style(self.font_style)
+            .stretch(self.font_stretch)
+    }
+    /// The name of the font file used for `font`, see `Resources::resolve_font`.
+    /// Locks `resources()`, so it's resolved once and passed on to the methods that need it.
+    pub fn font_name(&self) -> String {
+        resources().resolve_font(&self.font_descriptor())
+    }
+    /// The byte index of the cursor, moved back onto the text if it was replaced since the cursor
+    /// was set, such as by `TextStyle::Text`
+    pub fn cursor_byte(&self) -> Option<usize> {
+        self.cursor.map(|cursor| char_boundary(&self.text, cursor))
+    }
+    /// The selected byte range, if any text is selected
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.selection_anchor.map(|anchor| char_boundary(&self.text, anchor));
+        match (self.cursor_byte(), anchor) {
+            (Some(cursor), Some(anchor)) if cursor != anchor => {
+                Some(cmp::min(cursor, anchor)..cmp::max(cursor, anchor))
+            }
+            _ => None,
+        }
+    }
+    /// The byte index of the cursor at `cursor` once moved by `movement`, with the text
+    /// wrapped to `width`. Graphemes and words are found per UAX #29, see `text_layout::segment`.
+    pub fn move_cursor(&self, cursor: usize, movement: CursorMovement, width: f32) -> usize {
+        let line_infos: Vec<LineInfo> = self.with_spans(&self.font_name(), &self.text, |spans, _| {
+            LineInfos::with_spans(&self.text, spans, self.wrap, width).collect()
+        });
+        let lines = || line_infos.iter().cloned();
+        let index = match cursor_index(&self.text, lines(), cursor) {
+            Some(index) => index,
+            None => return char_boundary(&self.text, cursor),
+        };
+        let moved = match movement {
+            CursorMovement::Previous => index.previous(&self.text, lines()),
+            CursorMovement::Next => index.next(&self.text, lines()),
+            CursorMovement::PreviousWord => index.previous_word_start(&self.text, lines()),
+            CursorMovement::NextWord => index.next_word_end(&self.text, lines()),
+            CursorMovement::LineStart => Some(Index { line: index.line, char: 0 }),
+            CursorMovement::LineEnd => lines().nth(index.line).map(|info| {
+                Index { line: index.line, char: info.char_range().len() }
+            }),
+        };
+        moved.map_or(cursor, |index| index_byte(&self.text, lines(), index))
     }
     pub fn min_height(&self) -> f32 {
         self.line_height()
     }
     pub fn line_height(&self) -> f32 {
-        self.font_size + self.v_metrics().line_gap
+        self.font_line_height(&self.font_name())
+    }
+    fn font_line_height(&self, font_name: &str) -> f32 {
+        self.font_size + self.v_metrics(font_name).line_gap
     }
     pub fn text_fits(&self, text: &str, bounds: Rect) -> bool {
-        let line_height = self.line_height();
-        let mut resources = resources();
-        let font = resources.get_font(&self.font);
-        let height = text_layout::get_text_height(
-            text,
-            &font.info,
-            self.font_size,
-            line_height,
-            self.wrap,
-            bounds.width());
+        let height = self.with_spans(&self.font_name(), text, |spans, _| {
+            span::get_text_height(text, spans, self.wrap, bounds.width())
+        });
         height <= bounds.height()
     }
-    fn get_line_rects(&self, bounds: Rect) -> Vec<Rect> {
-        let line_height = self.line_height();
+    /// Runs `f` with the spans of `text` drawn by `font_name` and each fallback font, see `font_spans`
+    fn with_spans<F, T>(&self, font_name: &str, text: &str, f: F) -> T
+        where F: FnOnce(&[Span], &[(usize, String)]) -> T
+    {
+        let runs = [(0..text.len(), font_name.to_owned(), self.font_size)];
+        font_spans(text, &runs, &self.fallback_fonts, f)
+    }
+    fn get_line_rects(&self, font_name: &str, bounds: Rect) -> Vec<Rect> {
+        let text = self.overflow_text(font_name, bounds.size);
+        let line_height = self.font_line_height(font_name);
         let mut resources = resources();
-        let font = resources.get_font(&self.font);
+        let font = resources.get_font(font_name);
         text_layout::get_line_rects(
-            &self.text,
+            &text,
             bounds,
             &font.info,
             self.font_size,
@@ -85,44 +205,89 @@ impl TextState {
             self.wrap,
             self.align)
     }
-    fn position_glyphs(&self, bounds: Rect) -> Vec<GlyphInstance> {
-        let line_height = self.line_height();
-        let descent = self.v_metrics().descent;
-        let mut resources = resources();
-        let font = resources.get_font(&self.font);
-        text_layout::get_positioned_glyphs(
-            &self.text,
-            bounds,
-            &font.info,
-            self.font_size,
-            line_height,
-            self.wrap,
-            self.align).iter().map(|glyph| {
-                let position = glyph.position();
-                GlyphInstance {
-                    index: glyph.id().0,
-                    point: LayoutPoint::new(position.x, position.y + descent),
-                }
-            }).collect()
+    /// Glyphs of the text, grouped by the font they are drawn with
+    fn position_glyphs(&self, font_name: &str, bounds: Rect) -> Vec<(String, Vec<GlyphInstance>)> {
+        let text = self.overflow_text(font_name, bounds.size);
+        self.with_spans(font_name, &text, |spans, fonts| {
+            let mut glyphs: Vec<(String, Vec<GlyphInstance>)> = Vec::new();
+            for run in span::get_glyph_runs(&text, spans, bounds, self.wrap, self.align) {
+                let font = &fonts[run.span].1;
+                let index = match glyphs.iter().position(|&(ref name, _)| name == font) {
+                    Some(index) => index,
+                    None => {
+                        glyphs.push((font.clone(), Vec::new()));
+                        glyphs.len() - 1
+                    }
+                };
+                glyphs[index].1.extend(run.glyphs.iter().map(|glyph| {
+                    let position = glyph.position();
+                    GlyphInstance {
+                        index: glyph.id().0,
+                        point: LayoutPoint::new(position.x, position.y),
+                    }
+                }));
+            }
+            glyphs
+        })
     }
-    fn font_instance_key(&self) -> FontInstanceKey {
-        *resources().get_font_instance(&self.font, self.font_size)
+    /// Draws the selection behind the text and the cursor, as laid out in `bounds`
+    fn draw_cursor(&self, font_name: &str, bounds: Rect, renderer: &mut RenderBuilder) {
+        // the cursor is an index into `text`, which isn't what's shown once it is cut off
+        let cursor = match self.cursor_byte() {
+            Some(cursor) if self.show_cursor && self.overflow == Overflow::Clip => cursor,
+            _ => return,
+        };
+        // selected chars, counted from the start of the text
+        let selection = self.selection().map(|selection| {
+            self.text[..selection.start].chars().count()..self.text[..selection.end].chars().count()
+        });
+        // laid out with the same spans as the glyphs, so the cursor lines up with fallback fonts
+        self.with_spans(font_name, &self.text, |spans, _| {
+            let line_infos: Vec<LineInfo> = LineInfos::with_spans(&self.text, spans, self.wrap, bounds.width())
+                .collect();
+            let runs = span::get_glyph_runs(&self.text, spans, bounds, self.wrap, self.align);
+            let cursor_at = cursor_index(&self.text, line_infos.iter().cloned(), cursor);
+            let mut top = bounds.top();
+            for (line, info) in line_infos.iter().enumerate() {
+                let height = span::line_metrics(spans, info.byte_range()).height;
+                let xs = cursor_xs(&self.text, info, &runs, bounds, self.align);
+                if let Some(ref selection) = selection {
+                    let start = cmp::max(selection.start, info.start_char);
+                    let end = cmp::min(selection.end, info.end_char());
+                    if start < end {
+                        // right-to-left chars in the selection can be displayed in any order
+                        let selected = &xs[start - info.start_char..end - info.start_char + 1];
+                        let left = selected.iter().cloned().fold(f32::MAX, f32::min);
+                        let right = selected.iter().cloned().fold(f32::MIN, f32::max);
+                        let rect = Rect::new(Point::new(left, top), Size::new(right - left, height));
+                        renderer.builder.push_rect(&PrimitiveInfo::new(rect.typed()), BLUE_HIGHLIGHT.into());
+                    }
+                }
+                match cursor_at {
+                    Some(index) if index.line == line => {
+                        let rect = Rect::new(Point::new(xs[index.char], top), Size::new(1.0, height));
+                        renderer.builder.push_rect(&PrimitiveInfo::new(rect.typed()), self.text_color.into());
+                    }
+                    _ => (),
+                }
+                top += height;
+            }
+        })
     }
-    fn v_metrics(&self) -> VMetrics {
+    fn v_metrics(&self, font_name: &str) -> VMetrics {
         let mut resources = resources();
-        let font = resources.get_font(&self.font);
+        let font = resources.get_font(font_name);
         font.info.v_metrics(Scale::uniform(self.font_size))
     }
 }
 
 impl Draw for TextState {
     fn draw(&mut self, bounds: Rect, _: Rect, renderer: &mut RenderBuilder) {
-        let glyphs = self.position_glyphs(bounds);
+        let font_name = self.font_name();
+        let glyphs_per_font = self.position_glyphs(&font_name, bounds);
         if DEBUG_LINE_BOUNDS {
-            let line_rects = self.get_line_rects(bounds);
-            let v_metrics = self.v_metrics();
-            let mut resources = resources();
-            let font = resources.get_font(&self.font);
+            let line_rects = self.get_line_rects(&font_name, bounds);
+            let v_metrics = self.v_metrics(&font_name);
             for mut rect in line_rects {
                 render::draw_rect_outline(rect, CYAN, renderer);
                 rect.origin.y = rect.bottom() + v_metrics.descent;
@@ -130,24 +295,57 @@ impl Draw for TextState {
                 render::draw_rect_outline(rect, RED, renderer);
             }
             let scale = Scale::uniform(self.font_size);
-            for glyph in &glyphs {
-                let scaled_glyph = font.info.glyph(GlyphId(glyph.index)).unwrap().scaled(scale);
-                if let Some(rect) = scaled_glyph.exact_bounding_box() {
-                    let origin = glyph.point.to_vector().to_untyped() + Vector::new(0.0, -1.0);
-                    let rect = Rect::from_rusttype(rect).translate(&origin);
-                    render::draw_rect_outline(rect, BLUE, renderer);
+            let mut resources = resources();
+            for &(ref font, ref glyphs) in &glyphs_per_font {
+                let font = resources.get_font(font);
+                for glyph in glyphs {
+                    let scaled_glyph = font.info.glyph(GlyphId(glyph.index)).unwrap().scaled(scale);
+                    if let Some(rect) = scaled_glyph.exact_bounding_box() {
+                        let origin = glyph.point.to_vector().to_untyped() + Vector::new(0.0, -1.0);
+                        let rect = Rect::from_rusttype(rect).translate(&origin);
+                        render::draw_rect_outline(rect, BLUE, renderer);
+                    }
                 }
             }
         }
-        let key = self.font_instance_key();
+        self.draw_cursor(&font_name, bounds, renderer);
         let info = PrimitiveInfo::new(bounds.typed());
-        renderer.builder.push_text(
-            &info,
-            &glyphs,
-            key,
-            self.text_color.into(),
-            None,
-        );
+        // one glyph run per font, so fallback fonts get their own font instance
+        for (font, glyphs) in glyphs_per_font {
+            let key = *resources().get_font_instance(&font, self.font_size);
+            renderer.builder.push_text(
+                &info,
+                &glyphs,
+                key,
+                self.text_color.into(),
+                None,
+            );
+        }
+    }
+    fn intrinsic_size(&self) -> Option<IntrinsicSize> {
+        let font_name = self.font_name();
+        let text_size = self.with_spans(&font_name, &self.text, |spans, _| {
+            span::get_text_size(&self.text, spans, self.wrap)
+        });
+        if self.wrap == Wrap::NoWrap && self.overflow == Overflow::Clip {
+            Some(IntrinsicSize::Exact(text_size))
+        } else {
+            let min_height = self.font_line_height(&font_name);
+            Some(IntrinsicSize::Wrapping { width: text_size.width, min_height: min_height })
+        }
+    }
+    fn baseline(&self) -> Option<f32> {
+        // the first line fits the ascent of any fallback fonts used in it
+        let end = self.text.find('\n').unwrap_or(self.text.len());
+        let font_name = self.font_name();
+        self.with_spans(&font_name, &self.text, |spans, _| Some(span::line_metrics(spans, 0..end).ascent))
+    }
+    fn height_for_width(&self, width: f32) -> Option<f32> {
+        if self.wrap == Wrap::NoWrap && self.overflow == Overflow::Clip {
+            None
+        } else {
+            Some(self.measure_height(width))
+        }
     }
 }
 
@@ -155,11 +353,17 @@ impl Draw for TextState {
 pub enum TextStyle {
     Text(Value<String>),
     Font(Value<String>),
+    FontWeight(Value<FontWeight>),
+    FontStyle(Value<FontStyle>),
+    FontStretch(Value<FontStretch>),
+    FallbackFonts(Value<Vec<String>>),
     FontSize(Value<f32>),
     TextColor(Value<Color>),
     BackgroundColor(Value<Color>),
     Wrap(Value<Wrap>),
     Align(Value<Align>),
+    Overflow(Value<Overflow>),
+    ShowCursor(Value<bool>),
 }
 
 impl Style<TextState> for TextStyle {
@@ -167,11 +371,129 @@ impl Style<TextState> for TextStyle {
         match *self {
             TextStyle::Text(ref val) => style::update(&mut state.text, val.get(props)),
             TextStyle::Font(ref val) => style::update(&mut state.font, val.get(props)),
+            TextStyle::FontWeight(ref val) => style::update(&mut state.font_weight, val.get(props)),
+            TextStyle::FontStyle(ref val) => style::update(&mut state.font_style, val.get(props)),
+            TextStyle::FontStretch(ref val) => style::update(&mut state.font_stretch, val.get(props)),
+            TextStyle::FallbackFonts(ref val) => style::update(&mut state.fallback_fonts, val.get(props)),
             TextStyle::FontSize(ref val) => style::update(&mut state.font_size, val.get(props)),
             TextStyle::TextColor(ref val) => style::update(&mut state.text_color, val.get(props)),
             TextStyle::BackgroundColor(ref val) => style::update(&mut state.background_color, val.get(props)),
             TextStyle::Wrap(ref val) => style::update(&mut state.wrap, val.get(props)),
             TextStyle::Align(ref val) => style::update(&mut state.align, val.get(props)),
+            TextStyle::Overflow(ref val) => style::update(&mut state.overflow, val.get(props)),
+            TextStyle::ShowCursor(ref val) => style::update(&mut state.show_cursor, val.get(props)),
         }
     }
 }
+
+/// The cursor `Index` before the char at byte index `byte` of `text`
+fn cursor_index<I>(text: &str, line_infos: I, byte: usize) -> Option<Index>
+    where I: Iterator<Item = LineInfo>
+{
+    cursor::index_before_char(line_infos, text[..char_boundary(text, byte)].chars().count())
+}
+
+/// `byte` limited to the length of `text` and moved back to the start of the char it is in
+fn char_boundary(text: &str, byte: usize) -> usize {
+    let mut byte = cmp::min(byte, text.len());
+    while !text.is_char_boundary(byte) {
+        byte -= 1;
+    }
+    byte
+}
+
+/// Every cursor position on the line `info`, in logical order, from the glyphs laid out by
+/// `span::get_glyph_runs`. Like `cursor::XysPerLine`, the cursor at each visual position is at the
+/// start of the glyph to its right, or after the last glyph at the end of the line.
+fn cursor_xs(text: &str, info: &LineInfo, runs: &[GlyphRun], bounds: Rect, align: Align) -> Vec<f32> {
+    let range = info.byte_range();
+    let line_runs: Vec<&GlyphRun> = runs.iter()
+        .filter(|run| run.byte_range.start >= range.start && run.byte_range.end <= range.end)
+        .collect();
+    let glyph_xs: Vec<f32> = line_runs.iter()
+        .flat_map(|run| run.glyphs.iter().map(|glyph| glyph.position().x))
+        .collect();
+    let bounding_x = text_layout::types::Range::new(bounds.left(), bounds.right());
+    let start = line::align_line(info, align, bounding_x).start;
+    let visual_xs: Vec<f32> = iter::once(start)
+        .chain(glyph_xs.iter().skip(1).cloned())
+        .chain(line_runs.last().map(|run| run.rect.right()))
+        .collect();
+    let bidi = BidiLine::new(&text[range]);
+    (0..bidi.len() + 1).map(|cursor| visual_xs[bidi.logical_to_visual(cursor)]).collect()
+}
+
+/// The byte index of the char after the cursor `index`, or the end of `text`
+fn index_byte<I>(text: &str, mut line_infos: I, index: Index) -> usize
+    where I: Iterator<Item = LineInfo>
+{
+    line_infos.nth(index.line)
+        .and_then(|info| text.char_indices().nth(info.start_char + index.char))
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+/// Splits each run of `text` into spans drawn by a single font, using the run's own font where it
+/// has glyphs and otherwise the first of `fallback_fonts`, then `Resources::fallback_fonts`,
+/// that does. Runs are given as a byte range, font name and font size.
+///
+/// `f` is called with the spans, along with the index of the run each span is from and the name
+/// of the font used for it. The fonts are copied out of `resources()`, which isn't locked while
+/// `f` runs.
+pub(super) fn font_spans<F, T>(text: &str, runs: &[(Range<usize>, String, f32)], fallback_fonts: &[String], f: F) -> T
+    where F: FnOnce(&[Span], &[(usize, String)]) -> T
+{
+    let (fallback_fonts, fonts) = {
+        let mut resources = resources();
+        let fallback_fonts: Vec<String> = fallback_fonts.iter()
+            .chain(resources.fallback_fonts.iter())
+            .cloned()
+            .collect();
+        let mut fonts: Vec<(String, text_layout::Font)> = Vec::new();
+        for name in runs.iter().map(|&(_, ref name, _)| name).chain(fallback_fonts.iter()) {
+            if !fonts.iter().any(|&(ref loaded, _)| loaded == name) {
+                fonts.push((name.clone(), resources.get_font(name).info.clone()));
+            }
+        }
+        (fallback_fonts, fonts)
+    };
+    let mut spans = Vec::new();
+    let mut span_fonts = Vec::new();
+    for (run_index, &(ref range, ref name, font_size)) in runs.iter().enumerate() {
+        let chain: Vec<&String> = iter::once(name).chain(fallback_fonts.iter()).collect();
+        let chain_fonts: Vec<&text_layout::Font> = chain.iter()
+            .map(|name| &fonts.iter().find(|&&(ref loaded, _)| loaded == *name).unwrap().1)
+            .collect();
+        for (split, font_index) in span::split_by_font(&text[range.clone()], &chain_fonts) {
+            spans.push(Span {
+                range: range.start + split.start..range.start + split.end,
+                font: chain_fonts[font_index],
+                font_size: font_size,
+            });
+            span_fonts.push((run_index, chain[font_index].clone()));
+        }
+    }
+    f(&spans, &span_fonts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_after_text_replaced() {
+        let mut state = TextState::new("h\u{e9}llo world");
+        state.cursor = Some(13);
+        state.selection_anchor = Some(3);
+        // shorter text, with the anchor inside the two byte char
+        state.text = "\u{e9}\u{e9}".to_owned();
+        assert_eq!(state.cursor_byte(), Some(4));
+        assert_eq!(state.selection(), Some(2..4));
+        assert_eq!(state.move_cursor(13, CursorMovement::Previous, 1000.0), 2);
+        assert_eq!(state.move_cursor(3, CursorMovement::Next, 1000.0), 4);
+
+        state.text = "".to_owned();
+        assert_eq!(state.cursor_byte(), Some(0));
+        assert_eq!(state.selection(), None);
+        assert_eq!(state.move_cursor(13, CursorMovement::LineStart, 1000.0), 0);
+    }
+}
@@ -1,6 +1,9 @@
 pub mod mouse;
 pub mod keyboard;
 
+use std::fs::File;
+use std::io::Write;
+
 use glutin;
 use glutin::ElementState;
 use webrender;
@@ -79,6 +82,15 @@ impl EventHandler<KeyboardInput> for DebugSettingsHandler {
         if let KeyboardInput(ElementState::Released, _, Some(glutin::VirtualKeyCode::F4)) = *event {
             ui.solver.debug_variables();
         }
+        // only while debug drawing is toggled on with F1, so a stray key press doesn't write files
+        if let KeyboardInput(ElementState::Released, _, Some(glutin::VirtualKeyCode::F5)) = *event {
+            if self.debug_on {
+                match File::create("layout.dot").and_then(|mut file| file.write_all(ui.solver.dot_graph().as_bytes())) {
+                    Ok(()) => info!("Wrote constraint graph to layout.dot"),
+                    Err(err) => warn!("Failed to write layout.dot: {}", err),
+                }
+            }
+        }
         if let KeyboardInput(ElementState::Released, _, Some(glutin::VirtualKeyCode::P)) = *event {
             ui.render.toggle_flags(webrender::PROFILER_DBG);
         }
@@ -13,6 +13,7 @@ pub struct MouseButton(pub glutin::ElementState, pub glutin::MouseButton);
 
 #[derive(Clone)]
 pub struct WidgetMouseWheel(pub glutin::MouseScrollDelta);
+pub struct WidgetMouseMoved(pub Point);
 pub struct WidgetMouseButton(pub glutin::ElementState, pub glutin::MouseButton);
 
 pub enum MouseInputEvent {
@@ -61,6 +62,9 @@ impl EventHandler<MouseInputEvent> for MouseController {
             MouseInputEvent::MouseMoved(mouse) => {
                 self.mouse = mouse;
                 self.check_widget_under_cursor(args);
+                if let Some(ref widget_under) = self.widget_under_mouse {
+                    widget_under.event_bubble_up(WidgetMouseMoved(mouse));
+                }
             }
             MouseInputEvent::MouseButton(state, button) => {
                 if let Some(ref widget_under) = self.widget_under_mouse {
@@ -1,13 +1,22 @@
 use std::ops::DerefMut;
+use std::mem;
+use std::collections::HashSet;
+
+use cassowary::strength::REQUIRED;
+use cassowary::WeightedRelation::EQ;
 
 use limn_layout::linear_layout::{LinearLayout, LinearLayoutSettings};
 use limn_layout::grid_layout::GridLayout;
+use limn_layout::flow_layout::{FlowLayout, FlowLayoutSettings};
 
 use resources::WidgetId;
 
-use app::App;
+use app::{App, FrameEvent};
+use ui::{Ui, BreakpointChanged};
+use event::{self, Target};
 
 use widget::{WidgetRef, WidgetBuilder};
+use widget::property::Property;
 
 pub use self::solver::LimnSolver;
 pub use limn_layout::*;
@@ -23,14 +32,84 @@ impl WidgetBuilder {
         self.layout().set_container(container);
         self
     }
+    pub fn flow_layout(&mut self, settings: FlowLayoutSettings) -> &mut Self {
+        self.layout().set_container(FlowLayout::new(settings));
+        self
+    }
+    /// Places the baseline of this widget at the baseline of `child`, so a widget that wraps
+    /// text, like a button, lines up with other text, see `ItemAlignment::Baseline`
+    pub fn baseline_from(&mut self, child: &mut WidgetBuilder) -> &mut Self {
+        let (child_top, child_baseline) = {
+            let mut layout = child.layout();
+            (layout.vars.top, layout.baseline())
+        };
+        {
+            let mut layout = self.layout();
+            let (top, baseline) = (layout.vars.top, layout.baseline());
+            layout.add(top + baseline | EQ(REQUIRED) | child_top + child_baseline);
+        }
+        self
+    }
+    /// Enables the constraint set `name` while the breakpoint that sets `property` is active,
+    /// and disables it otherwise, see `Ui::add_breakpoint` and `Layout::add_set`
+    pub fn enable_set_for(&mut self, property: Property, name: &str) -> &mut Self {
+        let changed_name = name.to_owned();
+        self.add_handler_fn(move |event: &BreakpointChanged, args| {
+            if event.property == property {
+                enable_set(&args.widget, &changed_name, event.active);
+            }
+        });
+        // breakpoints that are already active when the widget is added don't send `BreakpointChanged`,
+        // so the set starts out matching the root's current properties, like the widget's style
+        let name = name.to_owned();
+        self.add_handler_fn(move |_: &SyncBreakpointSet, args| {
+            let active = args.ui.get_root().props().contains(&property);
+            enable_set(&args.widget, &name, active);
+        });
+        self.widget.event(SyncBreakpointSet);
+        self
+    }
 }
 
+fn enable_set(widget: &WidgetRef, name: &str, enabled: bool) {
+    widget.update_layout(|layout| {
+        if enabled {
+            layout.enable_set(name);
+        } else {
+            layout.disable_set(name);
+        }
+    });
+}
+
+/// Sent by `enable_set_for` once the widget is built, to apply the breakpoints already active
+struct SyncBreakpointSet;
+
 #[derive(Clone)]
 pub struct UpdateLayout(pub WidgetRef);
 pub struct ResizeWindow;
 pub struct LayoutChanged(pub Vec<(usize, VarType, f64)>);
 pub struct LayoutUpdated;
 
+/// While a batch is open, `UpdateLayout` events are queued instead of being applied
+/// to the solver, see `batch_layout_updates`.
+pub struct BeginLayoutBatch;
+pub struct EndLayoutBatch;
+
+/// Queues the layout updates made by `f` and applies them in a single solver pass,
+/// sending one `LayoutChanged` event for all of the resulting changes.
+/// Batches can be nested, the updates are applied when the outermost batch ends.
+pub fn batch_layout_updates<F: FnOnce()>(f: F) {
+    event::event(Target::Root, BeginLayoutBatch);
+    f();
+    event::event(Target::Root, EndLayoutBatch);
+}
+
+impl Ui {
+    fn update_layout(&mut self, widget_ref: &WidgetRef) {
+        self.solver.update_layout(&mut widget_ref.widget_mut().layout);
+    }
+}
+
 impl App {
     pub fn add_layout_handlers(&mut self) {
         self.add_handler_fn(|_: &ResizeWindow, args| {
@@ -39,33 +118,107 @@ impl App {
         self.add_handler_fn(|event: &UpdateLayout, args| {
             let event = event.clone();
             let UpdateLayout(widget_ref) = event;
-            let mut widget_mut = widget_ref.widget_mut();
-            let layout = &mut widget_mut.layout;
-            args.ui.solver.update_layout(layout);
-            args.ui.check_layout_changes();
+            if args.ui.layout_batch_depth > 0 {
+                if !args.ui.layout_batch.contains(&widget_ref) {
+                    args.ui.layout_batch.push(widget_ref);
+                }
+            } else {
+                args.ui.update_layout(&widget_ref);
+                args.ui.check_layout_changes();
+            }
+        });
+        self.add_handler_fn(|_: &BeginLayoutBatch, args| {
+            args.ui.layout_batch_depth += 1;
+        });
+        self.add_handler_fn(|_: &EndLayoutBatch, args| {
+            if args.ui.layout_batch_depth > 0 {
+                args.ui.layout_batch_depth -= 1;
+            }
+            if args.ui.layout_batch_depth == 0 {
+                let batch = mem::replace(&mut args.ui.layout_batch, Vec::new());
+                for widget_ref in &batch {
+                    args.ui.update_layout(widget_ref);
+                }
+                args.ui.check_layout_changes();
+            }
         });
         self.add_handler_fn(|event: &LayoutChanged, args| {
             let changes = &event.0;
+            let mut containers = HashSet::new();
+            let mut transitions = HashSet::new();
+            let mut resized = HashSet::new();
             for &(widget_id, var, value) in changes {
                 let widget_id = WidgetId(widget_id);
                 if let Some(widget) = args.ui.get_widget(widget_id) {
                     {
                         let widget = &mut *widget.widget_mut();
+                        // animate from where the widget was drawn before any of this change was applied
+                        if !transitions.contains(&widget_id) && widget.start_transition() {
+                            transitions.insert(widget_id);
+                        }
                         let value = value as f32;
                         debug!("{:?}: {:?} = {}", widget.name(), var, value);
                         match var {
                             VarType::Left => widget.bounds.origin.x = value,
                             VarType::Top => widget.bounds.origin.y = value,
-                            VarType::Width => widget.bounds.size.width = value,
+                            VarType::Width => {
+                                widget.bounds.size.width = value;
+                                resized.insert(widget_id);
+                            }
                             VarType::Height => widget.bounds.size.height = value,
                             _ => (),
                         }
                     }
                     widget.event(LayoutUpdated);
+                    if let Some(parent) = widget.parent() {
+                        containers.insert(parent);
+                    }
+                    containers.insert(widget);
+                }
+            }
+            // containers that depend on solved bounds, like FlowLayout, may need to update their constraints
+            for widget_ref in containers {
+                let changed = {
+                    let ui = &*args.ui;
+                    let widget_id = widget_ref.id();
+                    let bounds = widget_ref.bounds();
+                    let get_bounds = |id: LayoutId| {
+                        if id == widget_id.0 {
+                            Some(bounds)
+                        } else {
+                            ui.get_widget(WidgetId(id)).map(|widget| widget.bounds())
+                        }
+                    };
+                    widget_ref.widget_mut().layout.layout_changed(&get_bounds)
+                };
+                if changed {
+                    event::event(Target::Root, UpdateLayout(widget_ref));
+                }
+            }
+            // wrapping content gets taller or shorter as its width changes, so it's measured
+            // again once the width is known and the layout solved again with the new height.
+            // Only once per widget until the events are handled, since the new height can change
+            // the width again, like a scroll bar appearing, which could otherwise never settle
+            for widget_id in resized {
+                if !args.ui.remeasured.insert(widget_id) {
+                    continue;
+                }
+                if let Some(widget_ref) = args.ui.get_widget(widget_id) {
+                    if widget_ref.widget_mut().update_height_for_width() {
+                        event::event(Target::Root, UpdateLayout(widget_ref));
+                    }
+                }
+            }
+            for widget_id in transitions {
+                if let Some(widget_ref) = args.ui.get_widget(widget_id) {
+                    args.ui.start_animating(widget_ref);
                 }
             }
             // redraw everything when layout changes, for now
             args.ui.redraw();
         });
+        self.add_handler_fn(|_: &FrameEvent, args| {
+            args.ui.update_animations();
+        });
     }
 }
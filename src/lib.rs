@@ -23,6 +23,10 @@ extern crate webrender;
 extern crate gleam;
 extern crate app_units;
 extern crate image;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
 
 #[macro_use]
 pub mod event;
@@ -41,6 +45,7 @@ pub mod input;
 pub mod prelude;
 pub mod render;
 pub mod window;
+pub mod description;
 
 #[cfg(not(feature="nightly"))]
 fn type_name<T>() -> &'static str {
@@ -5,14 +5,15 @@ pub use geometry::{Point, PointExt, Rect, RectExt, Size, SizeExt, Vector};
 pub use event::{Target, EventHandler, EventArgs};
 pub use event::{event, event_global};
 pub use widget::{WidgetRef, WidgetBuilder};
-pub use widget::draw::{Draw, DrawEventHandler};
+pub use widget::draw::{Draw, DrawEventHandler, IntrinsicSize};
+pub use widget::animation::{Easing, LayoutAnimation};
 pub use widget::property::Property;
 pub use widget::property::states::*;
 pub use render::RenderBuilder;
 pub use resources::WidgetId;
-pub use ui::Ui;
+pub use ui::{Ui, Breakpoint, BreakpointChanged};
 pub use app::FrameEvent;
 pub use color::*;
 pub use layout::constraint::*;
-pub use layout::LAYOUT;
+pub use layout::{LAYOUT, Direction};
 pub use layout::linear_layout::{LinearLayoutSettings, Orientation, Spacing, ItemAlignment};
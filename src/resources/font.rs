@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+
+/// Font weight on the CSS scale, from 100 (thin) to 900 (black)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FontWeight(pub u16);
+
+impl FontWeight {
+    pub const THIN: FontWeight = FontWeight(100);
+    pub const LIGHT: FontWeight = FontWeight(300);
+    pub const NORMAL: FontWeight = FontWeight(400);
+    pub const MEDIUM: FontWeight = FontWeight(500);
+    pub const BOLD: FontWeight = FontWeight(700);
+    pub const BLACK: FontWeight = FontWeight(900);
+}
+
+impl Default for FontWeight {
+    fn default() -> Self {
+        FontWeight::NORMAL
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontStyle {
+    Normal,
+    Italic,
+}
+
+impl Default for FontStyle {
+    fn default() -> Self {
+        FontStyle::Normal
+    }
+}
+
+/// How condensed or expanded the glyphs of a font are
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FontStretch {
+    UltraCondensed,
+    ExtraCondensed,
+    Condensed,
+    SemiCondensed,
+    Normal,
+    SemiExpanded,
+    Expanded,
+    ExtraExpanded,
+    UltraExpanded,
+}
+
+impl Default for FontStretch {
+    fn default() -> Self {
+        FontStretch::Normal
+    }
+}
+
+/// Describes a font by family and face, resolved to a font file by a `FontRegistry`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontDescriptor {
+    pub family: String,
+    pub weight: FontWeight,
+    pub style: FontStyle,
+    pub stretch: FontStretch,
+}
+
+impl FontDescriptor {
+    /// The regular face of `family`
+    pub fn new(family: &str) -> Self {
+        FontDescriptor {
+            family: family.to_owned(),
+            weight: FontWeight::default(),
+            style: FontStyle::default(),
+            stretch: FontStretch::default(),
+        }
+    }
+    pub fn weight(mut self, weight: FontWeight) -> Self {
+        self.weight = weight;
+        self
+    }
+    pub fn style(mut self, style: FontStyle) -> Self {
+        self.style = style;
+        self
+    }
+    pub fn stretch(mut self, stretch: FontStretch) -> Self {
+        self.stretch = stretch;
+        self
+    }
+}
+
+/// A face of a family and the name of its font file, relative to `assets/fonts`
+#[derive(Debug, Clone)]
+struct FontFace {
+    weight: FontWeight,
+    style: FontStyle,
+    stretch: FontStretch,
+    name: String,
+}
+
+/// Maps font families to the font files of their faces
+#[derive(Debug, Default)]
+pub struct FontRegistry {
+    families: HashMap<String, Vec<FontFace>>,
+}
+
+impl FontRegistry {
+    pub fn new() -> Self {
+        FontRegistry::default()
+    }
+    /// Registry with the families bundled in `assets/fonts`
+    pub fn with_bundled_fonts() -> Self {
+        let mut registry = FontRegistry::new();
+        for family in &["NotoSans", "Hack"] {
+            let faces = [
+                ("Regular", FontWeight::NORMAL, FontStyle::Normal),
+                ("Italic", FontWeight::NORMAL, FontStyle::Italic),
+                ("Bold", FontWeight::BOLD, FontStyle::Normal),
+                ("BoldItalic", FontWeight::BOLD, FontStyle::Italic),
+            ];
+            for &(face, weight, style) in &faces {
+                let descriptor = FontDescriptor::new(family).weight(weight).style(style);
+                registry.add(&descriptor, &format!("{}/{}-{}", family, family, face));
+            }
+        }
+        registry
+    }
+    /// Registers the font file `name` as the face of a family given by `descriptor`,
+    /// replacing any face already registered for it.
+    pub fn add(&mut self, descriptor: &FontDescriptor, name: &str) {
+        let faces = self.families.entry(descriptor.family.clone()).or_insert_with(Vec::new);
+        faces.retain(|face| {
+            (face.weight, face.style, face.stretch) != (descriptor.weight, descriptor.style, descriptor.stretch)
+        });
+        faces.push(FontFace {
+            weight: descriptor.weight,
+            style: descriptor.style,
+            stretch: descriptor.stretch,
+            name: name.to_owned(),
+        });
+    }
+    pub fn has_family(&self, family: &str) -> bool {
+        self.families.contains_key(family)
+    }
+    /// The name of the font file for the face of the family that best matches `descriptor`,
+    /// or `None` if the family isn't registered.
+    ///
+    /// Similarly to CSS font matching, the closest stretch is chosen first, then the style,
+    /// then the closest weight. Between weights equally far away, lighter weights are preferred
+    /// for normal or lighter text and heavier ones for bolder text.
+    pub fn resolve(&self, descriptor: &FontDescriptor) -> Option<&str> {
+        let faces = self.families.get(&descriptor.family)?;
+        let heavier = descriptor.weight > FontWeight::MEDIUM;
+        faces.iter().min_by_key(|face| {
+            let stretch = (face.stretch as i32 - descriptor.stretch as i32).abs();
+            let style = face.style != descriptor.style;
+            let weight = (i32::from(face.weight.0) - i32::from(descriptor.weight.0)).abs();
+            let wrong_side = if heavier { face.weight < descriptor.weight } else { face.weight > descriptor.weight };
+            (stretch, style, weight, wrong_side)
+        }).map(|face| face.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(faces: &[(u16, FontStyle, &str)]) -> FontRegistry {
+        let mut registry = FontRegistry::new();
+        for &(weight, style, name) in faces {
+            registry.add(&FontDescriptor::new("Test").weight(FontWeight(weight)).style(style), name);
+        }
+        registry
+    }
+
+    fn resolve(registry: &FontRegistry, weight: u16, style: FontStyle) -> Option<&str> {
+        registry.resolve(&FontDescriptor::new("Test").weight(FontWeight(weight)).style(style))
+    }
+
+    #[test]
+    fn exact_match() {
+        let registry = registry(&[
+            (400, FontStyle::Normal, "Regular"),
+            (400, FontStyle::Italic, "Italic"),
+            (700, FontStyle::Normal, "Bold"),
+        ]);
+        assert_eq!(resolve(&registry, 400, FontStyle::Normal), Some("Regular"));
+        assert_eq!(resolve(&registry, 400, FontStyle::Italic), Some("Italic"));
+        assert_eq!(resolve(&registry, 700, FontStyle::Normal), Some("Bold"));
+    }
+
+    #[test]
+    fn nearest_weight() {
+        let registry = registry(&[
+            (300, FontStyle::Normal, "Light"),
+            (500, FontStyle::Normal, "Medium"),
+            (700, FontStyle::Normal, "Bold"),
+        ]);
+        assert_eq!(resolve(&registry, 100, FontStyle::Normal), Some("Light"));
+        assert_eq!(resolve(&registry, 650, FontStyle::Normal), Some("Bold"));
+        assert_eq!(resolve(&registry, 900, FontStyle::Normal), Some("Bold"));
+        // equally far away, lighter for weights up to 500 and heavier above
+        assert_eq!(resolve(&registry, 400, FontStyle::Normal), Some("Light"));
+        assert_eq!(resolve(&registry, 600, FontStyle::Normal), Some("Bold"));
+    }
+
+    #[test]
+    fn italic_falls_back_to_normal() {
+        let normal = registry(&[
+            (400, FontStyle::Normal, "Regular"),
+            (700, FontStyle::Normal, "Bold"),
+        ]);
+        assert_eq!(resolve(&normal, 400, FontStyle::Italic), Some("Regular"));
+        assert_eq!(resolve(&normal, 700, FontStyle::Italic), Some("Bold"));
+        // the style is matched before the weight
+        let mixed = registry(&[
+            (400, FontStyle::Italic, "Italic"),
+            (700, FontStyle::Normal, "Bold"),
+        ]);
+        assert_eq!(resolve(&mixed, 700, FontStyle::Italic), Some("Italic"));
+    }
+
+    #[test]
+    fn unknown_family() {
+        let registry = FontRegistry::with_bundled_fonts();
+        assert_eq!(registry.resolve(&FontDescriptor::new("Missing")), None);
+        assert_eq!(registry.resolve(&FontDescriptor::new("NotoSans")), Some("NotoSans/NotoSans-Regular"));
+    }
+}
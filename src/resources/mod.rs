@@ -1,5 +1,6 @@
 #[macro_use]
 pub mod id;
+pub mod font;
 
 use std::sync::{Mutex, MutexGuard};
 use std::collections::HashMap;
@@ -12,6 +13,7 @@ use app_units;
 use text_layout;
 
 use self::id::{Id, IdGen};
+use self::font::{FontDescriptor, FontRegistry};
 
 pub type Font = rusttype::Font<'static>;
 
@@ -68,6 +70,10 @@ impl<I: Id, T> Map<I, T> {
 pub struct Resources {
     pub render: Option<RenderApi>,
     pub fonts: HashMap<String, FontInfo>,
+    /// Font families, resolved to the font files loaded into `fonts`
+    pub font_registry: FontRegistry,
+    /// Fonts tried in order for characters that a text's own fonts have no glyph for
+    pub fallback_fonts: Vec<String>,
     pub font_instances: HashMap<(String, app_units::Au), FontInstanceKey>,
     pub images: HashMap<String, ImageInfo>,
     pub texture_descriptors: HashMap<u64, ImageDescriptor>,
@@ -78,6 +84,8 @@ impl Resources {
         Resources {
             render: None,
             fonts: HashMap::new(),
+            font_registry: FontRegistry::with_bundled_fonts(),
+            fallback_fonts: Vec::new(),
             font_instances: HashMap::new(),
             images: HashMap::new(),
             texture_descriptors: HashMap::new(),
@@ -117,6 +125,12 @@ impl Resources {
         &self.images[name]
     }
 
+    /// The name of the font file for `descriptor`. Families that aren't registered
+    /// are taken to be the name of a font file themselves.
+    pub fn resolve_font(&self, descriptor: &FontDescriptor) -> String {
+        self.font_registry.resolve(descriptor).unwrap_or(&descriptor.family).to_owned()
+    }
+
     pub fn get_font(&mut self, name: &str) -> &FontInfo {
         if !self.fonts.contains_key(name) {
             let data = load_font_data(name).unwrap();
@@ -11,9 +11,10 @@ use glutin;
 use window::Window;
 use app::App;
 use widget::{WidgetRef, WidgetBuilder};
+use widget::property::Property;
 use layout::{LimnSolver, LayoutChanged, LayoutVars, ExactFrame};
 use layout::constraint::*;
-use geometry::{Point, Rect, Size};
+use geometry::{Point, Rect, Size, Vector};
 use resources::WidgetId;
 use event::Target;
 use render::WebRenderContext;
@@ -32,6 +33,12 @@ pub struct Ui {
     debug_draw_bounds: bool,
     window: Rc<RefCell<Window>>,
     window_constraints: Vec<Constraint>,
+    pub(crate) layout_batch_depth: usize,
+    pub(crate) layout_batch: Vec<WidgetRef>,
+    // widgets measured again for a new width since the event queue was last empty, see `LayoutChanged`
+    pub(crate) remeasured: HashSet<WidgetId>,
+    breakpoints: Vec<Breakpoint>,
+    animating: Vec<WidgetRef>,
 }
 
 impl Ui {
@@ -52,6 +59,11 @@ impl Ui {
             debug_draw_bounds: false,
             window: Rc::new(RefCell::new(window)),
             window_constraints: Vec::new(),
+            layout_batch_depth: 0,
+            layout_batch: Vec::new(),
+            remeasured: HashSet::new(),
+            breakpoints: Vec::new(),
+            animating: Vec::new(),
         }
     }
 
@@ -71,6 +83,11 @@ impl Ui {
         self.should_close = true;
     }
 
+    /// Change the mouse cursor shown over the window
+    pub fn set_cursor(&mut self, cursor: glutin::MouseCursor) {
+        self.window.borrow().set_cursor(cursor);
+    }
+
     pub(super) fn should_close(&self) -> bool {
         self.should_close
     }
@@ -103,12 +120,51 @@ impl Ui {
                 layout.edit_bottom().set(window_dims.height).strength(REQUIRED - 1.0);
             });
         }
+        self.update_breakpoints(window_dims);
         self.needs_redraw = true;
     }
 
+    /// Adds a property to the root widget while the window size is within the breakpoint.
+    /// Several breakpoints can share a property, which is then set while any of them apply.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+        let window_size = self.window.borrow().size_f32();
+        self.update_breakpoints(Size::new(window_size.width, window_size.height));
+    }
+
+    fn update_breakpoints(&mut self, window_dims: Size) {
+        let mut root = self.get_root();
+        // breakpoints sharing a property are combined, it is set while the window is in any of them
+        let mut properties: Vec<Property> = Vec::new();
+        for breakpoint in &self.breakpoints {
+            if !properties.contains(&breakpoint.property) {
+                properties.push(breakpoint.property);
+            }
+        }
+        for property in properties {
+            let active = self.breakpoints.iter()
+                .any(|breakpoint| breakpoint.property == property && breakpoint.contains(window_dims));
+            if active != root.props().contains(&property) {
+                if active {
+                    root.add_prop(property);
+                } else {
+                    root.remove_prop(property);
+                }
+                root.event_subtree(BreakpointChanged {
+                    property: property,
+                    active: active,
+                });
+            }
+        }
+    }
+
     pub fn check_layout_changes(&mut self) {
 
         let changes = self.solver.fetch_changes();
+        // conflicts are only known once the whole update is applied, see `LimnSolver::take_errors`
+        for error in self.solver.take_errors() {
+            self.event(error);
+        }
         debug!("layout has {} changes", changes.len());
         if !changes.is_empty() {
             self.event(LayoutChanged(changes));
@@ -119,6 +175,23 @@ impl Ui {
         self.needs_redraw = true;
     }
 
+    /// Keep redrawing every frame until the layout animation of `widget_ref` finishes
+    pub(crate) fn start_animating(&mut self, widget_ref: WidgetRef) {
+        if !self.animating.contains(&widget_ref) {
+            self.animating.push(widget_ref);
+        }
+    }
+
+    /// Called on every `FrameEvent`, requests another frame while any widget is animating
+    pub(crate) fn update_animations(&mut self) {
+        if self.animating.is_empty() {
+            return;
+        }
+        // finished widgets still need one more frame to be drawn at their final bounds
+        self.redraw();
+        self.animating.retain(|widget_ref| widget_ref.is_animating());
+    }
+
     pub fn needs_redraw(&self) -> bool {
         self.needs_redraw
     }
@@ -135,9 +208,9 @@ impl Ui {
         let (builder, resources) = {
             let mut renderer = self.render.render_builder(window_size);
             let crop_to = Rect::new(Point::zero(), Size::new(::std::f32::MAX, ::std::f32::MAX));
-            self.root.widget_mut().draw(crop_to, &mut renderer);
+            self.root.widget_mut().draw(crop_to, Vector::zero(), &mut renderer);
             if self.debug_draw_bounds {
-                self.root.widget_mut().draw_debug(&mut renderer);
+                self.root.widget_mut().draw_debug(Vector::zero(), &mut renderer);
             }
             (renderer.builder, renderer.resources)
         };
@@ -221,6 +294,58 @@ impl Ui {
     }
 }
 
+/// A range of window sizes, for which `property` is set on the root widget
+/// and inherited by all widgets, so that styles and layouts can adapt to the window size.
+/// Ranges include the minimum and exclude the maximum.
+#[derive(Clone, Debug)]
+pub struct Breakpoint {
+    pub property: Property,
+    pub min_width: Option<f32>,
+    pub max_width: Option<f32>,
+    pub min_height: Option<f32>,
+    pub max_height: Option<f32>,
+}
+impl Breakpoint {
+    pub fn new(property: Property) -> Self {
+        Breakpoint {
+            property: property,
+            min_width: None,
+            max_width: None,
+            min_height: None,
+            max_height: None,
+        }
+    }
+    pub fn min_width(mut self, width: f32) -> Self {
+        self.min_width = Some(width);
+        self
+    }
+    pub fn max_width(mut self, width: f32) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+    pub fn min_height(mut self, height: f32) -> Self {
+        self.min_height = Some(height);
+        self
+    }
+    pub fn max_height(mut self, height: f32) -> Self {
+        self.max_height = Some(height);
+        self
+    }
+    pub fn contains(&self, size: Size) -> bool {
+        self.min_width.map_or(true, |min| size.width >= min) &&
+            self.max_width.map_or(true, |max| size.width < max) &&
+            self.min_height.map_or(true, |min| size.height >= min) &&
+            self.max_height.map_or(true, |max| size.height < max)
+    }
+}
+
+/// Sent to every widget when the window size enters or leaves a `Breakpoint`
+#[derive(Clone, Debug)]
+pub struct BreakpointChanged {
+    pub property: Property,
+    pub active: bool,
+}
+
 #[derive(Clone)]
 pub struct RegisterWidget(pub WidgetRef);
 #[derive(Clone)]
@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use geometry::{Rect, Point, Size};
+
+/// Maps the linear progress of an animation onto a curve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Eased progress for `t` in the range 0.0 to 1.0
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.max(0.0).min(1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Describes how a widget moves to its new bounds when its layout changes
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutAnimation {
+    pub duration: Duration,
+    pub easing: Easing,
+}
+
+impl LayoutAnimation {
+    pub fn new(duration: Duration, easing: Easing) -> Self {
+        LayoutAnimation {
+            duration: duration,
+            easing: easing,
+        }
+    }
+}
+
+/// A transition in progress, from the bounds the widget was drawn at when the layout changed
+/// towards its current layout bounds.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BoundsTransition {
+    from: Rect,
+    start: Instant,
+}
+
+impl BoundsTransition {
+    pub(super) fn new(from: Rect) -> Self {
+        BoundsTransition {
+            from: from,
+            start: Instant::now(),
+        }
+    }
+    /// Progress of the transition, between 0.0 and 1.0, before easing
+    pub(super) fn progress(&self, animation: &LayoutAnimation) -> f32 {
+        let elapsed = duration_secs(self.start.elapsed());
+        let duration = duration_secs(animation.duration);
+        if duration <= 0.0 {
+            1.0
+        } else {
+            (elapsed / duration).min(1.0)
+        }
+    }
+    pub(super) fn bounds(&self, to: Rect, animation: &LayoutAnimation) -> Rect {
+        let t = animation.easing.apply(self.progress(animation));
+        let lerp = |from: f32, to: f32| from + (to - from) * t;
+        Rect::new(
+            Point::new(lerp(self.from.origin.x, to.origin.x), lerp(self.from.origin.y, to.origin.y)),
+            Size::new(lerp(self.from.size.width, to.size.width), lerp(self.from.size.height, to.size.height)))
+    }
+}
+
+fn duration_secs(duration: Duration) -> f32 {
+    duration.as_secs() as f32 + duration.subsec_nanos() as f32 / 1_000_000_000.0
+}
@@ -8,7 +8,12 @@ use event::{EventHandler, EventArgs};
 use widget::property::PropSet;
 use widget::style::Style;
 
-use geometry::{Rect, Point};
+use cassowary::Constraint;
+use cassowary::strength::STRONG;
+
+use layout::LayoutVars;
+use layout::constraint::*;
+use geometry::{Rect, Point, Size};
 
 
 pub trait Draw: Downcast {
@@ -16,9 +21,50 @@ pub trait Draw: Downcast {
     fn is_under_cursor(&self, bounds: Rect, cursor: Point) -> bool {
         bounds.contains(&cursor)
     }
+    /// Preferred height when laid out at `width`, for content like wrapping text whose height
+    /// depends on its width. Re-measured whenever the solved width changes.
+    fn height_for_width(&self, _width: f32) -> Option<f32> {
+        None
+    }
+    /// Size of the content, kept in sync with the size constraints of the widget whenever the
+    /// draw state or its style changes.
+    fn intrinsic_size(&self) -> Option<IntrinsicSize> {
+        None
+    }
+    /// Distance from the top of the bounds to the baseline of the first line of text,
+    /// see `Layout::baseline`
+    fn baseline(&self) -> Option<f32> {
+        None
+    }
 }
 impl_downcast!(Draw);
 
+/// How the content of a draw state constrains the size of its widget, see `Draw::intrinsic_size`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntrinsicSize {
+    /// The widget is exactly the size of its content
+    Exact(Size),
+    /// The widget is at least the size of its content
+    Min(Size),
+    /// Content that wraps onto more lines when it gets narrower. The widget prefers `width` and
+    /// is at least `min_height` tall, its height then comes from `Draw::height_for_width`.
+    Wrapping { width: f32, min_height: f32 },
+}
+
+impl IntrinsicSize {
+    pub fn constraints(&self, vars: &LayoutVars) -> Vec<Constraint> {
+        match *self {
+            IntrinsicSize::Exact(content_size) => size(content_size).build(vars),
+            IntrinsicSize::Min(content_size) => min_size(content_size).build(vars),
+            IntrinsicSize::Wrapping { width: preferred_width, min_height: content_min_height } => {
+                let mut constraints = width(preferred_width).strength(STRONG).build(vars);
+                constraints.extend(min_height(content_min_height).build(vars));
+                constraints
+            }
+        }
+    }
+}
+
 type StyleFn = Fn(&mut Draw, &Any, &PropSet) -> bool;
 
 pub(super) struct DrawStyle {
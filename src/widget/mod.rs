@@ -2,6 +2,7 @@
 pub mod style;
 pub mod property;
 pub mod draw;
+pub mod animation;
 
 use std::any::{TypeId, Any};
 use std::collections::HashMap;
@@ -10,21 +11,28 @@ use std::cell::{RefCell, Ref, RefMut};
 use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 use std::fmt;
+use std::mem;
+use std::time::Duration;
+
+use cassowary::Constraint;
+use cassowary::strength::{STRONG, REQUIRED};
+use cassowary::WeightedRelation::EQ;
 
 use render::RenderBuilder;
 use event::{self, EventHandler, EventArgs, EventHandlerWrapper};
-use layout::{Layout, LayoutVars, LayoutRef};
+use layout::{Layout, LayoutVars, LayoutRef, Direction};
 use ui::Ui;
 use resources::{resources, WidgetId};
-use geometry::{Point, Rect, RectExt};
+use geometry::{Point, Rect, RectExt, Vector};
 use render;
 use color::Color;
 use event::Target;
 use layout::UpdateLayout;
 
 use self::property::{PropSet, Property};
-use self::draw::{Draw, DrawWrapper};
+use self::draw::{Draw, DrawWrapper, IntrinsicSize};
 use self::style::Style;
+use self::animation::{LayoutAnimation, BoundsTransition, Easing};
 
 #[derive(Clone)]
 pub struct WidgetRef(pub Rc<RefCell<Widget>>);
@@ -75,6 +83,23 @@ impl WidgetRef {
         }
         self.apply_style();
     }
+    /// Sets the reading direction of this widget and its descendants, which mirrors logical
+    /// constraints like `align_start` and horizontal linear layouts.
+    /// Children added later take on the direction of their parent.
+    /// The subtree is updated in a single layout batch, since the constraints of each widget only
+    /// agree with those of its parent once both have the new direction.
+    pub fn set_direction(&mut self, direction: Direction) -> &mut Self {
+        ::layout::batch_layout_updates(|| {
+            self.update_layout(|layout| layout.set_direction(direction));
+            for mut child in self.children() {
+                child.set_direction(direction);
+            }
+        });
+        self
+    }
+    pub fn direction(&self) -> Direction {
+        self.0.borrow().layout.direction()
+    }
     pub fn draw_state(&mut self) -> DrawStateGuard {
         DrawStateGuard { guard: self.0.borrow_mut() }
     }
@@ -109,13 +134,38 @@ impl WidgetRef {
     pub fn bounds(&self) -> Rect {
         self.0.borrow().bounds
     }
+    /// The bounds the widget is currently drawn at, which lag behind `bounds` while a layout
+    /// animation is running. Children are also moved by the animation of their ancestors.
+    pub fn draw_bounds(&self) -> Rect {
+        self.0.borrow().draw_bounds()
+    }
+    pub fn is_animating(&self) -> bool {
+        self.0.borrow().is_animating()
+    }
+    pub fn set_layout_animation(&mut self, animation: Option<LayoutAnimation>) -> &mut Self {
+        {
+            let mut widget = self.widget_mut();
+            widget.layout_animation = animation;
+            if animation.is_none() {
+                widget.transition = None;
+            }
+        }
+        self
+    }
 
     pub fn update<F, T: Draw + 'static>(&mut self, f: F)
         where F: FnOnce(&mut T)
     {
         self.0.borrow_mut().update(f);
+        self.update_intrinsic_size();
         self.event(self::style::StyleUpdated);
     }
+    /// Re-measures the draw state and updates the size constraints, see `Draw::intrinsic_size`
+    pub fn update_intrinsic_size(&self) {
+        if self.0.borrow_mut().update_intrinsic_size() {
+            event::event(Target::Root, UpdateLayout(self.clone()));
+        }
+    }
     pub fn update_layout<F>(&self, f: F)
         where F: FnOnce(&mut Layout)
     {
@@ -126,6 +176,7 @@ impl WidgetRef {
 
     pub fn apply_style(&mut self) {
         if self.0.borrow_mut().apply_style() {
+            self.update_intrinsic_size();
             self.event(self::style::StyleUpdated);
         }
     }
@@ -136,6 +187,10 @@ impl WidgetRef {
         child.widget_mut().parent = Some(self.downgrade());
         child.widget_mut().props.extend(self.props().iter().cloned());
         child.apply_style();
+        let direction = self.direction();
+        if child.direction() != direction {
+            child.set_direction(direction);
+        }
         self.widget_mut().children.push(child.clone());
         self.update_layout(|layout| {
             child.update_layout(|child_layout| {
@@ -314,6 +369,13 @@ pub struct Widget {
     has_updated: bool,
     pub(super) layout: Layout,
     pub(super) bounds: Rect,
+    layout_animation: Option<LayoutAnimation>,
+    transition: Option<BoundsTransition>,
+    intrinsic_size: Option<IntrinsicSize>,
+    intrinsic_size_constraints: Vec<Constraint>,
+    baseline: Option<f32>,
+    measured_height: Option<f32>,
+    height_for_width: Vec<Constraint>,
     name: String,
     debug_color: Option<Color>,
     children: Vec<WidgetRef>,
@@ -331,6 +393,13 @@ impl Widget {
             layout: Layout::new(id.0, Some(name.clone())),
             has_updated: false,
             bounds: Rect::zero(),
+            layout_animation: None,
+            transition: None,
+            intrinsic_size: None,
+            intrinsic_size_constraints: Vec::new(),
+            baseline: None,
+            measured_height: None,
+            height_for_width: Vec::new(),
             name: name,
             debug_color: None,
             children: Vec::new(),
@@ -344,8 +413,14 @@ impl Widget {
     pub fn layout(&mut self) -> &mut Layout {
         &mut self.layout
     }
-    pub fn draw(&mut self, crop_to: Rect, renderer: &mut RenderBuilder) {
-        let bounds = self.bounds;
+    /// Draws this widget and its children, moved by `offset`, the distance the ancestors of this
+    /// widget are drawn from their layout bounds while they animate
+    pub fn draw(&mut self, crop_to: Rect, offset: Vector, renderer: &mut RenderBuilder) {
+        let bounds = self.draw_bounds().translate(&offset);
+        let child_offset = self.child_offset(offset);
+        if !self.is_animating() {
+            self.transition = None;
+        }
         let clip_id = renderer.builder.define_clip(None, bounds.typed(), vec![], None);
         renderer.builder.push_clip_id(clip_id);
         if let Some(draw_state) = self.draw_state.as_mut() {
@@ -354,19 +429,103 @@ impl Widget {
         if let Some(crop_to) = crop_to.intersection(&bounds) {
             for child in &self.children {
                 let mut child = child.widget_mut();
-                child.draw(crop_to, renderer);
+                child.draw(crop_to, child_offset, renderer);
             }
         }
         renderer.builder.pop_clip_id();
     }
-    pub fn draw_debug(&mut self, renderer: &mut RenderBuilder) {
+    pub fn draw_debug(&mut self, offset: Vector, renderer: &mut RenderBuilder) {
         let color = self.debug_color.unwrap_or(::color::GREEN);
-        render::draw_rect_outline(self.bounds, color, renderer);
+        render::draw_rect_outline(self.draw_bounds().translate(&offset), color, renderer);
+        let child_offset = self.child_offset(offset);
         for child in &self.children {
-            child.widget_mut().draw_debug(renderer);
+            child.widget_mut().draw_debug(child_offset, renderer);
+        }
+    }
+    /// Children are laid out inside the layout bounds, so they're drawn moved along with the
+    /// animated bounds of this widget
+    fn child_offset(&self, offset: Vector) -> Vector {
+        offset + (self.draw_bounds().origin - self.bounds.origin)
+    }
+
+    pub fn draw_bounds(&self) -> Rect {
+        match (self.transition, self.layout_animation) {
+            (Some(transition), Some(animation)) => transition.bounds(self.bounds, &animation),
+            _ => self.bounds,
+        }
+    }
+    pub fn is_animating(&self) -> bool {
+        match (self.transition, self.layout_animation) {
+            (Some(transition), Some(animation)) => transition.progress(&animation) < 1.0,
+            _ => false,
+        }
+    }
+    /// Starts a transition from the currently drawn bounds if the widget has a layout animation.
+    /// Called before the layout bounds are updated. Returns true if a transition was started.
+    pub(super) fn start_transition(&mut self) -> bool {
+        // the first layout places the widget, there is nothing to animate from
+        if self.layout_animation.is_none() || self.bounds == Rect::zero() {
+            return false;
         }
+        self.transition = Some(BoundsTransition::new(self.draw_bounds()));
+        true
     }
 
+    /// Replaces the size and baseline constraints from the previous measurement of the draw state.
+    /// Returns true if they changed.
+    fn update_intrinsic_size(&mut self) -> bool {
+        let (intrinsic_size, baseline) = match self.draw_state {
+            Some(ref draw_state) => (draw_state.state.intrinsic_size(), draw_state.state.baseline()),
+            None => (None, None),
+        };
+        // content may have changed without changing its size, in which case only the height
+        // for the current width needs measuring again
+        let height_changed = self.update_height_for_width();
+        if intrinsic_size == self.intrinsic_size && baseline == self.baseline {
+            return height_changed;
+        }
+        let constraints = mem::replace(&mut self.intrinsic_size_constraints, Vec::new());
+        self.layout.remove_constraints(constraints);
+        let mut constraints = Vec::new();
+        if let Some(intrinsic_size) = intrinsic_size {
+            constraints.extend(intrinsic_size.constraints(&self.layout.vars));
+        }
+        if let Some(baseline) = baseline {
+            constraints.push(self.layout.baseline() | EQ(REQUIRED) | baseline);
+        }
+        self.layout.add(constraints.clone());
+        self.intrinsic_size_constraints = constraints;
+        self.intrinsic_size = intrinsic_size;
+        self.baseline = baseline;
+        true
+    }
+    /// Re-measures content whose height depends on its width, see `Draw::height_for_width`.
+    /// Returns true if the height constraint changed and the layout needs to be solved again.
+    pub(super) fn update_height_for_width(&mut self) -> bool {
+        let width = self.bounds.size.width;
+        let height = match self.draw_state {
+            // not laid out yet, wait for the first solved width
+            Some(ref draw_state) if width > 0.0 => draw_state.state.height_for_width(width),
+            _ => None,
+        };
+        match (self.measured_height, height) {
+            (None, None) => return false,
+            (Some(old), Some(new)) if (old - new).abs() < 0.5 => return false,
+            _ => (),
+        }
+        let constraints = mem::replace(&mut self.height_for_width, Vec::new());
+        self.layout.remove_constraints(constraints);
+        if let Some(height) = height {
+            let constraints = vec![self.layout.vars.height | EQ(STRONG) | height];
+            self.layout.add(constraints.clone());
+            self.height_for_width = constraints;
+        }
+        self.measured_height = height;
+        true
+    }
+
+    /// Hit testing uses the layout bounds, not the animated ones, so input goes to where the
+    /// widget is ending up.
     pub fn is_under_cursor(&self, cursor: Point) -> bool {
         if let Some(ref draw_state) = self.draw_state {
             draw_state.is_under_cursor(self.bounds, cursor)
@@ -421,12 +580,14 @@ impl WidgetBuilder {
     pub fn set_draw_state<T: Draw + 'static>(&mut self, draw_state: T) -> &mut Self {
         self.widget.widget_mut().draw_state = Some(DrawWrapper::new(draw_state));
         self.widget.widget_mut().apply_style();
+        self.widget.update_intrinsic_size();
         self.widget.event(self::style::StyleUpdated);
         self
     }
     pub fn set_draw_state_with_style<T: Draw + 'static, S: Style<T> + 'static>(&mut self, draw_state: T, style: S) -> &mut Self {
         self.widget.widget_mut().draw_state = Some(DrawWrapper::new_with_style(draw_state, style));
         self.widget.widget_mut().apply_style();
+        self.widget.update_intrinsic_size();
         self.widget.event(self::style::StyleUpdated);
         self
     }
@@ -458,6 +619,15 @@ impl WidgetBuilder {
         self.widget.widget_mut().layout.name = Some(name.to_owned());
         self
     }
+    pub fn set_direction(&mut self, direction: Direction) -> &mut Self {
+        self.widget.set_direction(direction);
+        self
+    }
+    /// Animate the drawn bounds of this widget whenever its layout changes
+    pub fn animate_layout(&mut self, duration: Duration, easing: Easing) -> &mut Self {
+        self.widget.set_layout_animation(Some(LayoutAnimation::new(duration, easing)));
+        self
+    }
 }
 
 impl Into<WidgetRef> for WidgetBuilder {
@@ -527,3 +697,34 @@ macro_rules! widget_builder {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use geometry::{Point, Rect, Size, Vector};
+    use super::WidgetBuilder;
+    use super::animation::{LayoutAnimation, Easing};
+
+    #[test]
+    fn child_moves_with_animating_parent() {
+        let mut parent = WidgetBuilder::new("parent");
+        let child = WidgetBuilder::new("child");
+        parent.animate_layout(Duration::from_secs(1000), Easing::Linear);
+        let (parent, child) = (parent.widget_ref(), child.widget_ref());
+
+        child.widget_mut().bounds = Rect::new(Point::new(10.0, 10.0), Size::new(20.0, 20.0));
+        {
+            let mut parent = parent.widget_mut();
+            parent.bounds = Rect::new(Point::new(100.0, 0.0), Size::new(50.0, 50.0));
+            assert!(parent.start_transition());
+            parent.bounds = Rect::new(Point::new(0.0, 0.0), Size::new(50.0, 50.0));
+        }
+
+        let offset = parent.widget_mut().child_offset(Vector::zero());
+        let child_bounds = child.draw_bounds().translate(&offset);
+        assert!((child_bounds.origin.x - 110.0).abs() < 0.1);
+        assert!((child_bounds.origin.y - 10.0).abs() < 0.1);
+        assert!(child_bounds.size == Size::new(20.0, 20.0));
+    }
+}
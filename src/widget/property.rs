@@ -8,6 +8,10 @@ pub enum Property {
     Pressed,
     Inactive,
     Focused,
+    /// Set on the root widget while a breakpoint for a small window is active, see `Breakpoint`
+    Compact,
+    /// Set on the root widget while a breakpoint for a large window is active, see `Breakpoint`
+    Wide,
 }
 pub type PropSet = BTreeSet<Property>;
 
@@ -21,5 +25,7 @@ pub mod states {
         pub static ref SELECTED: PropSet = btreeset!{Property::Selected};
         pub static ref INACTIVE: PropSet = btreeset!{Property::Inactive};
         pub static ref FOCUSED: PropSet = btreeset!{Property::Focused};
+        pub static ref COMPACT: PropSet = btreeset!{Property::Compact};
+        pub static ref WIDE: PropSet = btreeset!{Property::Wide};
     }
 }
@@ -95,7 +95,7 @@ impl ToggleButtonBuilder {
 
         ToggleButtonBuilder { widget: widget }
     }
-    pub fn set_text(&mut self, on_text: &'static str, off_text: &'static str) -> &mut Self {
+    pub fn set_text(&mut self, on_text: &str, off_text: &str) -> &mut Self {
 
         let style = style!(parent: STYLE_BUTTON_TEXT,
             TextStyle::Text: selector!(off_text.to_owned(),
@@ -110,6 +110,7 @@ impl ToggleButtonBuilder {
             bound_bottom(&self.widget).padding(10.0),
             center(&self.widget),
         ]);
+        self.widget.baseline_from(&mut button_text_widget);
 
         self.widget.add_child(button_text_widget);
         self
@@ -141,7 +142,7 @@ impl PushButtonBuilder {
 
         PushButtonBuilder { widget: widget }
     }
-    pub fn set_text(&mut self, text: &'static str) -> &mut Self {
+    pub fn set_text(&mut self, text: &str) -> &mut Self {
 
         let style = style!(parent: STYLE_BUTTON_TEXT,
             TextStyle::Text: text.to_owned(),
@@ -156,6 +157,7 @@ impl PushButtonBuilder {
             bound_bottom(&self.widget).padding(10.0),
             center(&self.widget),
         ]);
+        self.widget.baseline_from(&mut button_text_widget);
 
         self.widget.add_child(button_text_widget);
         self
@@ -1,14 +1,14 @@
-use cassowary::Constraint;
+use glutin::{ElementState, VirtualKeyCode};
+
+use text_layout::segment;
 
-use layout::constraint::ConstraintBuilder;
 use layout::constraint::*;
-use widget::style::StyleUpdated;
 use widget::WidgetBuilder;
 use widget::property::states::*;
 use ui::{WidgetAttachedEvent, WidgetDetachedEvent};
-use input::keyboard::{WidgetReceivedCharacter, KeyboardInputEvent};
+use input::keyboard::{WidgetReceivedCharacter, WidgetKeyboardInput, KeyboardInputEvent};
 use draw::rect::{RectState, RectStyle};
-use draw::text::TextState;
+use draw::text::{TextState, TextStyle, CursorMovement};
 use event::{EventHandler, EventArgs};
 use color::*;
 
@@ -16,34 +16,125 @@ const BACKSPACE: char = '\u{8}';
 
 fn edit_text_handle_char(event: &WidgetReceivedCharacter, mut args: EventArgs) {
     let &WidgetReceivedCharacter(char) = event;
-    let text = {
+    let (text, cursor) = {
         let bounds = args.widget.bounds();
         let draw_state = args.widget.draw_state();
         let text_draw_state = draw_state.downcast_ref::<TextState>().unwrap();
         let mut text = text_draw_state.text.clone();
+        let cursor = text_draw_state.cursor_byte().unwrap_or(text.len());
+        // typing replaces the selection
+        let selection = text_draw_state.selection().unwrap_or(cursor..cursor);
         match char {
+            BACKSPACE if selection.start == selection.end => {
+                // removes a whole grapheme cluster, such as a letter with its accents
+                let start = segment::previous_grapheme_boundary(&text, cursor);
+                text.drain(start..cursor);
+                (text, start)
+            }
             BACKSPACE => {
-                text.pop();
+                text.drain(selection.clone());
+                (text, selection.start)
             }
+            // such as delete and control key combinations, handled as key input instead
+            _ if char.is_control() => return,
             _ => {
-                text.push(char);
-                if !text_draw_state.text_fits(&text, bounds) {
-                    text.pop();
+                text.drain(selection.clone());
+                text.insert(selection.start, char);
+                if text_draw_state.text_fits(&text, bounds) {
+                    (text, selection.start + char.len_utf8())
+                } else {
+                    return;
                 }
             }
         }
-        text
     };
+    set_text(&mut args, text, cursor);
+}
+
+fn set_text(args: &mut EventArgs, text: String, cursor: usize) {
     args.widget.update(|state: &mut TextState| {
-        state.text = text.clone()
+        state.text = text.clone();
+        state.cursor = Some(cursor);
+        state.selection_anchor = None;
     });
-    args.widget.event(TextUpdated(text.clone()));
+    args.widget.event(TextUpdated(text));
+}
+
+/// Moves the cursor with the arrow, home and end keys, by word while control is held,
+/// selecting the text passed over while shift is held. Delete removes the grapheme cluster
+/// after the cursor, or the selection.
+struct EditTextKeyHandler {
+    shift: bool,
+    control: bool,
+}
+
+impl EditTextKeyHandler {
+    fn new() -> Self {
+        EditTextKeyHandler {
+            shift: false,
+            control: false,
+        }
+    }
+}
+
+impl EventHandler<WidgetKeyboardInput> for EditTextKeyHandler {
+    fn handle(&mut self, event: &WidgetKeyboardInput, mut args: EventArgs) {
+        let &WidgetKeyboardInput(state, _, key) = event;
+        let pressed = state == ElementState::Pressed;
+        let movement = match key {
+            Some(VirtualKeyCode::LShift) | Some(VirtualKeyCode::RShift) => {
+                self.shift = pressed;
+                return;
+            }
+            Some(VirtualKeyCode::LControl) | Some(VirtualKeyCode::RControl) => {
+                self.control = pressed;
+                return;
+            }
+            _ if !pressed => return,
+            Some(VirtualKeyCode::Left) if self.control => CursorMovement::PreviousWord,
+            Some(VirtualKeyCode::Left) => CursorMovement::Previous,
+            Some(VirtualKeyCode::Right) if self.control => CursorMovement::NextWord,
+            Some(VirtualKeyCode::Right) => CursorMovement::Next,
+            Some(VirtualKeyCode::Home) => CursorMovement::LineStart,
+            Some(VirtualKeyCode::End) => CursorMovement::LineEnd,
+            Some(VirtualKeyCode::Delete) => {
+                let (text, cursor) = {
+                    let draw_state = args.widget.draw_state();
+                    let text_draw_state = draw_state.downcast_ref::<TextState>().unwrap();
+                    let mut text = text_draw_state.text.clone();
+                    let cursor = text_draw_state.cursor_byte().unwrap_or(text.len());
+                    let selection = text_draw_state.selection().unwrap_or_else(|| {
+                        cursor..segment::next_grapheme_boundary(&text, cursor)
+                    });
+                    text.drain(selection.clone());
+                    (text, selection.start)
+                };
+                set_text(&mut args, text, cursor);
+                return;
+            }
+            _ => return,
+        };
+        let width = args.widget.bounds().width();
+        let shift = self.shift;
+        args.widget.update(|state: &mut TextState| {
+            let cursor = state.cursor_byte().unwrap_or(state.text.len());
+            state.selection_anchor = if shift { Some(state.selection_anchor.unwrap_or(cursor)) } else { None };
+            state.cursor = Some(state.move_cursor(cursor, movement, width));
+        });
+    }
 }
 
 pub struct TextUpdated(pub String);
 
 pub fn text_change_handle(event: &TextUpdated, mut args: EventArgs) {
-    args.widget.update(|state: &mut TextState| state.text = event.0.clone());
+    args.widget.update(|state: &mut TextState| {
+        // text set from elsewhere puts the cursor at the end
+        if state.text != event.0 {
+            state.text = event.0.clone();
+            state.cursor = Some(state.text.len());
+            state.selection_anchor = None;
+        }
+    });
 }
 
 pub struct EditTextBuilder {
@@ -70,15 +161,18 @@ impl EditTextBuilder {
             .make_focusable();
 
         let mut text_widget = WidgetBuilder::new("edit_text_text");
+        let mut text_state = TextState::default();
+        text_state.cursor = Some(0);
         text_widget
-            .set_draw_state(TextState::default())
-            .add_handler(TextUpdatedHandler::default())
+            .set_draw_state_with_style(text_state, style!(TextStyle::ShowCursor: selector!(false, FOCUSED: true)))
             .add_handler_fn(edit_text_handle_char)
+            .add_handler(EditTextKeyHandler::new())
             .add_handler_fn(text_change_handle);
 
         text_widget.layout().add(constraints![
             align_left(&widget).padding(5.0),
             align_top(&widget).padding(5.0),
+            match_width(&widget).padding(10.0),
             bound_by(&widget).padding(5.0),
         ]);
 
@@ -103,27 +197,3 @@ impl Into<WidgetBuilder> for EditTextBuilder {
         self.widget
     }
 }
-
-#[derive(Default)]
-struct TextUpdatedHandler {
-    size_constraints: Vec<Constraint>,
-}
-impl EventHandler<StyleUpdated> for TextUpdatedHandler {
-    fn handle(&mut self, _: &StyleUpdated, mut args: EventArgs) {
-        args.widget.update_layout(|layout| {
-            for constraint in self.size_constraints.drain(..) {
-                layout.remove_constraint(constraint);
-            }
-        });
-        let line_height = {
-            let draw_state = args.widget.draw_state();
-            let text_draw_state = draw_state.downcast_ref::<TextState>().unwrap();
-            text_draw_state.line_height()
-        };
-        let size_constraints = min_height(line_height).build(&args.widget.layout_vars());
-        args.widget.update_layout(|layout| {
-            layout.add(size_constraints.clone())
-        });
-        self.size_constraints = size_constraints;
-    }
-}
@@ -0,0 +1,165 @@
+use std::ops::Range;
+
+use glutin::MouseCursor;
+
+use event::{EventArgs, EventHandler};
+use widget::{WidgetBuilder, WidgetRef};
+use draw::rich_text::{RichTextState, StyledRange, TextAttributes};
+use input::mouse::{ClickEvent, MouseOverEvent, WidgetMouseMoved};
+use geometry::Point;
+use color::*;
+
+static COLOR_LINK: Color = BLUE_HIGHLIGHT;
+static COLOR_LINK_MOUSEOVER: Color = BLUE;
+
+/// Sent when a link is clicked, bubbles up from the text widget containing the link
+#[derive(Debug, Clone)]
+pub struct LinkClicked(pub String);
+
+/// A byte range of the text that acts as a link, and the payload sent when it is clicked
+#[derive(Debug, Clone)]
+pub struct Link {
+    pub range: Range<usize>,
+    pub payload: String,
+}
+
+enum LinkEvent {
+    MouseMoved(Point),
+    MouseOut,
+    Clicked(Point),
+}
+
+struct LinkHandler {
+    links: Vec<Link>,
+    /// Index of the first link's styled range in the draw state
+    first_range: usize,
+    hovered: Option<usize>,
+}
+
+impl LinkHandler {
+    fn link_at(&self, widget: &WidgetRef, point: Point) -> Option<usize> {
+        let bounds = widget.bounds();
+        let byte = widget.widget().draw_state::<RichTextState>()
+            .and_then(|state| state.byte_index_at(bounds, point));
+        byte.and_then(|byte| {
+            self.links.iter().position(|link| link.range.start <= byte && byte < link.range.end)
+        })
+    }
+    fn set_hovered(&mut self, hovered: Option<usize>, args: &mut EventArgs) {
+        if hovered == self.hovered {
+            return;
+        }
+        let (first_range, old_hovered) = (self.first_range, self.hovered);
+        args.widget.update(|state: &mut RichTextState| {
+            if let Some(link) = old_hovered {
+                state.ranges[first_range + link].attributes.text_color = Some(COLOR_LINK);
+            }
+            if let Some(link) = hovered {
+                state.ranges[first_range + link].attributes.text_color = Some(COLOR_LINK_MOUSEOVER);
+            }
+        });
+        let cursor = if hovered.is_some() { MouseCursor::Hand } else { MouseCursor::Default };
+        args.ui.set_cursor(cursor);
+        self.hovered = hovered;
+    }
+}
+
+impl EventHandler<LinkEvent> for LinkHandler {
+    fn handle(&mut self, event: &LinkEvent, mut args: EventArgs) {
+        match *event {
+            LinkEvent::MouseMoved(point) => {
+                let hovered = self.link_at(&args.widget, point);
+                self.set_hovered(hovered, &mut args);
+            }
+            LinkEvent::MouseOut => self.set_hovered(None, &mut args),
+            LinkEvent::Clicked(point) => {
+                if let Some(link) = self.link_at(&args.widget, point) {
+                    args.widget.event_bubble_up(LinkClicked(self.links[link].payload.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Text where byte ranges can be marked as links, see `LinkClicked`
+pub struct LinkTextBuilder {
+    widget: WidgetBuilder,
+    state: RichTextState,
+    links: Vec<Link>,
+}
+widget_builder!(LinkTextBuilder);
+
+impl LinkTextBuilder {
+    pub fn new(state: RichTextState) -> Self {
+        LinkTextBuilder {
+            widget: WidgetBuilder::new("link_text"),
+            state: state,
+            links: Vec::new(),
+        }
+    }
+    /// Marks the text in the byte `range` as a link, that sends `payload` when clicked
+    pub fn add_link(&mut self, range: Range<usize>, payload: &str) -> &mut Self {
+        self.links.push(Link {
+            range: range,
+            payload: payload.to_owned(),
+        });
+        self
+    }
+    /// Appends `text` without a link
+    pub fn push(&mut self, text: &str) -> &mut Self {
+        self.state.push_plain(text);
+        self
+    }
+    /// Appends `text` as a link
+    pub fn push_link(&mut self, text: &str, payload: &str) -> &mut Self {
+        let start = self.state.text.len();
+        self.state.push_plain(text);
+        let end = self.state.text.len();
+        self.add_link(start..end, payload)
+    }
+    pub fn on_link_clicked<F>(&mut self, on_link_clicked: F) -> &mut Self
+        where F: Fn(&str, &mut EventArgs) + 'static
+    {
+        self.widget.add_handler_fn(move |event: &LinkClicked, mut args| {
+            (on_link_clicked)(&event.0, &mut args);
+            *args.handled = true;
+        });
+        self
+    }
+}
+
+impl Into<WidgetBuilder> for LinkTextBuilder {
+    fn into(self) -> WidgetBuilder {
+        let LinkTextBuilder { mut widget, mut state, links } = self;
+        let first_range = state.ranges.len();
+        for link in &links {
+            state.ranges.push(StyledRange {
+                range: link.range.clone(),
+                attributes: TextAttributes {
+                    text_color: Some(COLOR_LINK),
+                    underline: Some(true),
+                    ..TextAttributes::default()
+                },
+            });
+        }
+        widget.set_draw_state(state);
+        widget
+            .add_handler(LinkHandler {
+                links: links,
+                first_range: first_range,
+                hovered: None,
+            })
+            .add_handler_fn(|event: &WidgetMouseMoved, args| {
+                args.widget.event(LinkEvent::MouseMoved(event.0));
+            })
+            .add_handler_fn(|event: &MouseOverEvent, args| {
+                if let MouseOverEvent::Out = *event {
+                    args.widget.event(LinkEvent::MouseOut);
+                }
+            })
+            .add_handler_fn(|event: &ClickEvent, args| {
+                args.widget.event(LinkEvent::Clicked(event.position));
+            });
+        widget
+    }
+}
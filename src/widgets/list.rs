@@ -7,6 +7,7 @@ use draw::rect::{RectState, RectStyle};
 use draw::text::TextStyle;
 use input::mouse::ClickEvent;
 use layout::constraint::*;
+use layout::batch_layout_updates;
 use layout::linear_layout::{LinearLayoutSettings, Orientation};
 use color::*;
 
@@ -104,13 +105,15 @@ impl ListBuilder {
         where C: Iterator<Item=I>,
               F: Fn(I, &mut ListBuilder) -> WidgetBuilder,
     {
-        for item in contents {
-            let mut widget = build(item, self);
-            widget
-                .set_name("list_item")
-                .list_item(&self.widget.widget_ref());
-            self.widget.add_child(widget);
-        }
+        batch_layout_updates(|| {
+            for item in contents {
+                let mut widget = build(item, self);
+                widget
+                    .set_name("list_item")
+                    .list_item(&self.widget.widget_ref());
+                self.widget.add_child(widget);
+            }
+        });
     }
 }
 
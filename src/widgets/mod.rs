@@ -7,3 +7,4 @@ pub mod edit_text;
 pub mod image;
 pub mod glcanvas;
 pub mod text;
+pub mod link;
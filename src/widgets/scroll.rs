@@ -1,13 +1,12 @@
 use glutin;
 use cassowary::strength::*;
-use cassowary::WeightedRelation::*;
 
 use layout::constraint::*;
 use event::{EventArgs, EventHandler};
 use widget::{WidgetBuilder, WidgetRef};
 use widgets::slider::{SliderBuilder, SetSliderValue};
 use geometry::{Size, Vector, Rect, RectExt};
-use layout::{LayoutUpdated, LAYOUT};
+use layout::{LayoutUpdated, Direction};
 use input::mouse::WidgetMouseWheel;
 use draw::rect::{RectState, RectStyle};
 use color::*;
@@ -24,6 +23,8 @@ impl ScrollBuilder {
 
         let mut content_holder = WidgetBuilder::new("content_holder");
         content_holder.layout().no_container();
+        // the size of the content holder is fixed by the scroll widget, so the content can be solved separately
+        content_holder.layout().isolate();
 
         ScrollBuilder {
             widget: widget,
@@ -42,16 +43,16 @@ impl ScrollBuilder {
         scrollbar_h.scrollbar_style();
         scrollbar_h.layout().add(constraints![
             align_bottom(&self.widget),
-            align_left(&self.widget),
+            align_start(&self.widget),
             align_below(&self.content_holder),
         ]);
         let mut scrollbar_v = SliderBuilder::new();
         scrollbar_v.set_name("scrollbar_v");
         scrollbar_v.make_vertical().scrollbar_style();
         scrollbar_v.layout().add(constraints![
-            align_right(&self.widget),
+            align_end(&self.widget),
             align_top(&self.widget),
-            align_to_right_of(&self.content_holder),
+            align_to_end_of(&self.content_holder),
         ]);
 
         let widget_ref = self.content_holder.widget_ref();
@@ -67,8 +68,8 @@ impl ScrollBuilder {
         corner.set_draw_state_with_style(RectState::new(), corner_style);
         corner.layout().add(constraints![
             align_bottom(&self.widget),
-            align_right(&self.widget),
-            align_to_right_of(&scrollbar_h),
+            align_end(&self.widget),
+            align_to_end_of(&scrollbar_h),
             align_below(&scrollbar_v),
             match_height(&scrollbar_h),
             match_width(&scrollbar_v),
@@ -93,14 +94,14 @@ impl Into<WidgetBuilder> for ScrollBuilder {
             match_layout(&self.widget).strength(STRONG)
         ]);
         {
-            let content_holder = &self.content_holder.layout().vars;
+            let content_holder = self.content_holder.layout().vars.clone();
             content.layout().add(constraints![
-                LAYOUT.left | LE(REQUIRED) | content_holder.left,
-                LAYOUT.top | LE(REQUIRED) | content_holder.top,
-                LAYOUT.left | EQ(WEAK) | content_holder.left,
-                LAYOUT.top | EQ(WEAK) | content_holder.top,
-                LAYOUT.right | GE(STRONG) | content_holder.right,
-                LAYOUT.bottom | GE(STRONG) | content_holder.bottom,
+                layout!(LAYOUT.start <= content_holder.start),
+                layout!(LAYOUT.top <= content_holder.top),
+                layout!(LAYOUT.start == content_holder.start @ WEAK),
+                layout!(LAYOUT.top == content_holder.top @ WEAK),
+                layout!(LAYOUT.end >= content_holder.end @ STRONG),
+                layout!(LAYOUT.bottom >= content_holder.bottom @ STRONG),
             ]);
         }
 
@@ -115,7 +116,7 @@ impl Into<WidgetBuilder> for ScrollBuilder {
         self.content_holder.add_child(content);
         if self.scrollbars.is_some() {
             self.content_holder.layout().add(constraints![
-                align_left(&self.widget),
+                align_start(&self.widget),
                 align_top(&self.widget),
             ]);
         } else {
@@ -197,11 +198,20 @@ impl ScrollParent {
         });
     }
     fn move_slider_x(&mut self) {
+        let offset_x = self.logical_offset_x(-self.offset.x / self.scrollable_area.width);
         if let Some(ref mut scrollbars) = self.scrollbars {
-            let offset_x = -self.offset.x / self.scrollable_area.width;
             scrollbars.scrollbar_h.event(SetSliderValue(offset_x));
         }
     }
+    // the horizontal scroll bar starts on the right for right to left content, where the
+    // content rests against the right edge of the container
+    fn logical_offset_x(&self, offset_x: f32) -> f32 {
+        if self.scrollable.direction() == Direction::RightToLeft {
+            1.0 - offset_x
+        } else {
+            offset_x
+        }
+    }
     fn move_slider_y(&mut self) {
         if let Some(ref mut scrollbars) = self.scrollbars {
             let offset_y = -self.offset.y / self.scrollable_area.height;
@@ -303,7 +313,7 @@ impl EventHandler<ScrollParentEvent> for ScrollParent {
                 }
             }
             ScrollParentEvent::ScrollBarMovedX(ref offset) => {
-                self.offset.x = -offset * self.scrollable_area.width;
+                self.offset.x = -self.logical_offset_x(*offset) * self.scrollable_area.width;
                 self.move_content_x();
             }
             ScrollParentEvent::ScrollBarMovedY(ref offset) => {
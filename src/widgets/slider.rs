@@ -3,7 +3,7 @@ use std::ops::Range;
 use cassowary::strength::*;
 
 use layout::constraint::*;
-use layout::LayoutUpdated;
+use layout::{LayoutUpdated, Direction};
 use input::mouse::ClickEvent;
 use event::{EventHandler, EventArgs};
 use widget::{WidgetBuilder, WidgetRef};
@@ -159,14 +159,14 @@ impl Into<WidgetBuilder> for SliderBuilder {
                 slider_bar_pre.layout().add(constraints![
                     height(bar_width),
                     center_vertical(&widget),
-                    align_left(&widget).padding(bar_padding),
-                    to_left_of(&slider_handle).padding(-bar_padding),
+                    align_start(&widget).padding(bar_padding),
+                    to_start_of(&slider_handle).padding(-bar_padding),
                 ]);
                 slider_bar_post.layout().add(constraints![
                     height(bar_width),
                     center_vertical(&widget),
-                    align_right(&widget).padding(bar_padding),
-                    to_right_of(&slider_handle).padding(-bar_padding),
+                    align_end(&widget).padding(bar_padding),
+                    to_end_of(&slider_handle).padding(-bar_padding),
                 ]);
                 slider_handle.layout().add(match_height(&widget));
 
@@ -278,8 +278,16 @@ impl SliderHandler {
         let handle_pos_range = self.slider_size() - handle_size;
         let slider_range = self.slider_range();
         let val = (handle_pos - handle_size / 2.0 - slider_range.start) / handle_pos_range;
+        let val = self.mirror(val);
         val * (self.range.end - self.range.start) + self.range.start
     }
+    // horizontal sliders run from right to left in right to left layouts
+    fn mirror(&self, val: f32) -> f32 {
+        match self.orientation {
+            Orientation::Horizontal if self.slider_ref.direction() == Direction::RightToLeft => 1.0 - val,
+            _ => val,
+        }
+    }
     fn slider_size(&self) -> f32 {
         if let Orientation::Horizontal = self.orientation {
             self.slider_ref.bounds().width()
@@ -318,7 +326,7 @@ impl SliderHandler {
         f32::min(f32::max(handle_pos, min), max)
     }
     fn update_handle_pos(&self, value: f32) {
-        let value = (value - self.range.start) / (self.range.end - self.range.start);
+        let value = self.mirror((value - self.range.start) / (self.range.end - self.range.start));
         let range_of_motion = self.slider_size() - self.handle_size();
         let handle_start = self.slider_range().start + value * range_of_motion;
         self.handle_ref.update_layout(|layout| {
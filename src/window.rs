@@ -44,6 +44,9 @@ impl Window {
     pub fn resize(&mut self, width: u32, height: u32) {
         self.window.set_inner_size(width, height);
     }
+    pub fn set_cursor(&self, cursor: glutin::MouseCursor) {
+        self.window.set_cursor(cursor);
+    }
     pub fn size_u32(&self) -> DeviceUintSize {
         let (width, height) = self.window.get_inner_size_pixels().unwrap();
         DeviceUintSize::new(width, height)
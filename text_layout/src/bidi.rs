@@ -0,0 +1,500 @@
+//! The Unicode Bidirectional Algorithm (UAX #9), applied to single lines of text.
+//!
+//! Explicit embedding, override and isolate controls are ignored, and each line is treated as its
+//! own paragraph, which covers mixed right-to-left and left-to-right text within a line.
+
+use rusttype::{Scale, ScaledGlyph};
+
+use line_break::SOFT_HYPHEN;
+use span::Fonts;
+
+/// Embedding level of a character, even levels are left-to-right and odd levels right-to-left.
+pub type Level = u8;
+
+/// The bidirectional character types from UAX #9, excluding the explicit formatting types
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BidiClass {
+    /// Left-to-right
+    L,
+    /// Right-to-left
+    R,
+    /// Right-to-left Arabic
+    AL,
+    /// European number
+    EN,
+    /// European number separator
+    ES,
+    /// European number terminator
+    ET,
+    /// Arabic number
+    AN,
+    /// Common number separator
+    CS,
+    /// Nonspacing mark
+    NSM,
+    /// Boundary neutral
+    BN,
+    /// Paragraph separator
+    B,
+    /// Segment separator
+    S,
+    /// Whitespace
+    WS,
+    /// Other neutrals
+    ON,
+}
+
+use self::BidiClass::*;
+
+/// The bidirectional type of `ch`, covering the common right-to-left scripts, numbers,
+/// separators and marks. Other letters are treated as left-to-right, and other symbols as neutral.
+pub fn bidi_class(ch: char) -> BidiClass {
+    match ch as u32 {
+        0x0009 | 0x000B | 0x001F => S,
+        0x000A | 0x000D | 0x001C..=0x001E | 0x0085 | 0x2029 => B,
+        0x000C | 0x0020 | 0x1680 | 0x2000..=0x200A | 0x2028 | 0x205F | 0x3000 => WS,
+        0x0000..=0x0008 | 0x000E..=0x001B | 0x007F..=0x0084 | 0x0086..=0x009F |
+        0x00AD | 0x200B..=0x200D | 0x202A..=0x202E | 0x2060..=0x2069 | 0xFEFF => BN,
+        0x0030..=0x0039 | 0x00B2 | 0x00B3 | 0x00B9 | 0x06F0..=0x06F9 | 0x2070..=0x2079 |
+        0x2080..=0x2089 | 0xFF10..=0xFF19 => EN,
+        0x002B | 0x002D | 0x207A | 0x207B | 0x208A | 0x208B | 0x2212 | 0xFE62 | 0xFE63 |
+        0xFF0B | 0xFF0D => ES,
+        0x0023..=0x0025 | 0x00A2..=0x00A5 | 0x00B0 | 0x00B1 | 0x0609 | 0x060A | 0x066A |
+        0x2030..=0x2034 | 0x20A0..=0x20CF | 0xFE5F | 0xFE69 | 0xFE6A | 0xFF03..=0xFF05 => ET,
+        0x0660..=0x0669 | 0x066B | 0x066C | 0x0600..=0x0605 => AN,
+        0x002C | 0x002E | 0x002F | 0x003A | 0x00A0 | 0x060C | 0x202F | 0x2044 | 0xFE50 |
+        0xFE52 | 0xFE55 | 0xFF0C | 0xFF0E | 0xFF0F | 0xFF1A => CS,
+        0x0300..=0x036F | 0x0483..=0x0489 | 0x0591..=0x05BD | 0x05BF | 0x05C1 | 0x05C2 |
+        0x05C4 | 0x05C5 | 0x05C7 | 0x0610..=0x061A | 0x064B..=0x065F | 0x0670 |
+        0x06D6..=0x06DC | 0x06DF..=0x06E4 | 0x06E7 | 0x06E8 | 0x06EA..=0x06ED |
+        0x0711 | 0x0730..=0x074A | 0x07A6..=0x07B0 | 0x07EB..=0x07F3 | 0x08D3..=0x08FF |
+        0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x20D0..=0x20FF | 0xFE00..=0xFE0F |
+        0xFE20..=0xFE2F => NSM,
+        0x0590..=0x05FF | 0x07C0..=0x085F | 0xFB1D..=0xFB4F | 0x10800..=0x10FFF |
+        0x1E800..=0x1EFFF => R,
+        0x0600..=0x07BF | 0x0860..=0x08FF | 0xFB50..=0xFDFF | 0xFE70..=0xFEFF => AL,
+        _ => if ch.is_alphabetic() || ch.is_numeric() { L } else { ON },
+    }
+}
+
+/// The glyph for `ch` in right-to-left text, for paired punctuation such as brackets
+pub fn mirror(ch: char) -> char {
+    match ch {
+        '(' => ')',
+        ')' => '(',
+        '<' => '>',
+        '>' => '<',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '«' => '»',
+        '»' => '«',
+        '‹' => '›',
+        '›' => '‹',
+        _ => ch,
+    }
+}
+
+fn is_strong(class: BidiClass) -> bool {
+    class == L || class == R || class == AL
+}
+
+fn is_neutral(class: BidiClass) -> bool {
+    class == B || class == S || class == WS || class == ON
+}
+
+fn is_rtl_level(level: Level) -> bool {
+    level % 2 == 1
+}
+
+fn direction_of(level: Level) -> BidiClass {
+    if is_rtl_level(level) { R } else { L }
+}
+
+/// The resolved levels and display order of the characters in a single line of text
+#[derive(Clone, Debug)]
+pub struct BidiLine {
+    base_level: Level,
+    levels: Vec<Level>,
+    /// Logical char index for each visual position, from left to right
+    order: Vec<usize>,
+}
+
+impl BidiLine {
+    pub fn new(line: &str) -> Self {
+        let classes: Vec<BidiClass> = line.chars().map(bidi_class).collect();
+        // P2, P3: the direction of the first strong character
+        let base_level = match classes.iter().cloned().find(|&class| is_strong(class)) {
+            Some(R) | Some(AL) => 1,
+            _ => 0,
+        };
+        if classes.iter().all(|&class| class != R && class != AL && class != AN) && base_level == 0 {
+            // no right-to-left text, so no reordering
+            return BidiLine {
+                base_level: 0,
+                levels: vec![0; classes.len()],
+                order: (0..classes.len()).collect(),
+            };
+        }
+        let levels = resolve_levels(&classes, base_level);
+        let order = reorder(&levels);
+        BidiLine {
+            base_level: base_level,
+            levels: levels,
+            order: order,
+        }
+    }
+    /// Whether the line's base direction is right-to-left
+    pub fn is_rtl(&self) -> bool {
+        is_rtl_level(self.base_level)
+    }
+    pub fn base_level(&self) -> Level {
+        self.base_level
+    }
+    /// Number of chars in the line
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+    /// The resolved level of the char at logical index `char_i`
+    pub fn level(&self, char_i: usize) -> Level {
+        self.levels[char_i]
+    }
+    /// Whether the char at logical index `char_i` is displayed right-to-left, and should be mirrored
+    pub fn is_rtl_char(&self, char_i: usize) -> bool {
+        is_rtl_level(self.levels[char_i])
+    }
+    /// The logical char index for each visual position, from left to right
+    pub fn visual_order(&self) -> &[usize] {
+        &self.order
+    }
+    /// The visual position of the cursor before the logical char `cursor`, counting cursor
+    /// positions from the left of the line. A cursor at the end of the line follows the last char.
+    pub fn logical_to_visual(&self, cursor: usize) -> usize {
+        let len = self.len();
+        if len == 0 {
+            return 0;
+        }
+        let visual_of = |char_i: usize| self.order.iter().position(|&i| i == char_i).unwrap();
+        if cursor < len {
+            // leading edge of the char after the cursor
+            let visual = visual_of(cursor);
+            if self.is_rtl_char(cursor) { visual + 1 } else { visual }
+        } else {
+            // trailing edge of the last char
+            let visual = visual_of(len - 1);
+            if self.is_rtl_char(len - 1) { visual } else { visual + 1 }
+        }
+    }
+    /// The logical cursor at the visual cursor position `visual`. Where the direction changes
+    /// two logical cursors share a visual position, so this prefers a cursor that
+    /// `logical_to_visual` maps back to `visual`, otherwise the edge of the char to its right.
+    pub fn visual_to_logical(&self, visual: usize) -> usize {
+        let len = self.len();
+        if len == 0 {
+            return 0;
+        }
+        if let Some(cursor) = (0..len + 1).find(|&cursor| self.logical_to_visual(cursor) == visual) {
+            return cursor;
+        }
+        if visual < len {
+            // the char to the right of the cursor
+            let char_i = self.order[visual];
+            if self.is_rtl_char(char_i) { char_i + 1 } else { char_i }
+        } else {
+            // the char to the left of the cursor
+            let char_i = self.order[len - 1];
+            if self.is_rtl_char(char_i) { char_i } else { char_i + 1 }
+        }
+    }
+}
+
+/// A glyph of a line laid out in display order, see `layout_line`
+pub struct VisualGlyph<'a> {
+    /// Logical index of the glyph's char within the line
+    pub char_i: usize,
+    /// Byte index of the glyph's char within the line
+    pub byte_i: usize,
+    /// Index of the span the char is in, see `Fonts::at`
+    pub span: usize,
+    /// Offset of the glyph from the start of the line
+    pub x: f32,
+    pub glyph: ScaledGlyph<'a>,
+}
+
+/// Lays out a single line in display order from left to right, with right-to-left chars mirrored.
+/// Soft hyphens are hidden, other than at the end of the line where they are drawn as a hyphen.
+/// `offset` is the byte index of the line within the text covered by `fonts`.
+///
+/// Kerning is applied between glyphs of the same span that are next to each other both visually
+/// and logically, taking the pair in logical order.
+///
+/// `word_spacing` is added to each space between words, see `line::justified_word_spacing`.
+pub fn layout_line<'a>(line: &str,
+                       bidi: &BidiLine,
+                       fonts: Fonts<'a>,
+                       offset: usize,
+                       word_spacing: f32)
+                       -> Vec<VisualGlyph<'a>> {
+    let chars: Vec<(usize, char)> = line.char_indices().collect();
+    // spaces between the first and last words of the line
+    let words_start = chars.iter().position(|&(_, ch)| !ch.is_whitespace()).unwrap_or(0);
+    let words_end = chars.iter().rposition(|&(_, ch)| !ch.is_whitespace()).unwrap_or(0);
+    let is_word_space = |char_i: usize| {
+        words_start < char_i && char_i < words_end && chars[char_i].1.is_whitespace()
+    };
+    let mut glyphs: Vec<VisualGlyph> = Vec::with_capacity(chars.len());
+    let mut x = 0.0;
+    for &char_i in bidi.visual_order() {
+        let (byte_i, ch) = chars[char_i];
+        let ch = if bidi.is_rtl_char(char_i) { mirror(ch) } else { ch };
+        // soft hyphens are only shown where the line breaks after them
+        let hidden = ch == SOFT_HYPHEN && char_i + 1 < chars.len();
+        let ch = match ch {
+            SOFT_HYPHEN if hidden => ' ',
+            SOFT_HYPHEN => '-',
+            _ => ch,
+        };
+        let (span, font, font_size) = fonts.at(offset + byte_i);
+        let scale = Scale::uniform(font_size);
+        let glyph = font.glyph(ch).unwrap().scaled(scale);
+        if let Some(last) = glyphs.last() {
+            if last.span == span {
+                if last.char_i + 1 == char_i {
+                    x += font.pair_kerning(scale, last.glyph.id(), glyph.id());
+                } else if char_i + 1 == last.char_i {
+                    x += font.pair_kerning(scale, glyph.id(), last.glyph.id());
+                }
+            }
+        }
+        let advance_width = if hidden { 0.0 } else { glyph.h_metrics().advance_width };
+        glyphs.push(VisualGlyph {
+            char_i: char_i,
+            byte_i: byte_i,
+            span: span,
+            x: x,
+            glyph: glyph,
+        });
+        x += advance_width;
+        if is_word_space(char_i) {
+            x += word_spacing;
+        }
+    }
+    glyphs
+}
+
+/// Resolves the level of each character, treating the line as a single isolating run sequence
+fn resolve_levels(classes: &[BidiClass], base_level: Level) -> Vec<Level> {
+    let sos = direction_of(base_level);
+    let mut types = classes.to_vec();
+
+    // X9: boundary neutrals are ignored, here they take the type of the preceding character
+    for i in 0..types.len() {
+        if types[i] == BN {
+            types[i] = if i == 0 { sos } else { types[i - 1] };
+        }
+    }
+    // W1: nonspacing marks take the type of the preceding character
+    for i in 0..types.len() {
+        if types[i] == NSM {
+            types[i] = if i == 0 { sos } else { types[i - 1] };
+        }
+    }
+    // W2: European numbers after Arabic letters are Arabic numbers
+    let mut last_strong = sos;
+    for class in types.iter_mut() {
+        match *class {
+            L | R | AL => last_strong = *class,
+            EN if last_strong == AL => *class = AN,
+            _ => (),
+        }
+    }
+    // W3
+    for class in types.iter_mut() {
+        if *class == AL {
+            *class = R;
+        }
+    }
+    // W4: single separators between numbers
+    for i in 1..types.len().saturating_sub(1) {
+        let (prev, next) = (types[i - 1], types[i + 1]);
+        if types[i] == ES && prev == EN && next == EN {
+            types[i] = EN;
+        } else if types[i] == CS && prev == next && (prev == EN || prev == AN) {
+            types[i] = prev;
+        }
+    }
+    // W5: terminators adjacent to European numbers
+    let mut i = 0;
+    while i < types.len() {
+        if types[i] == ET {
+            let start = i;
+            while i < types.len() && types[i] == ET {
+                i += 1;
+            }
+            let before = start > 0 && types[start - 1] == EN;
+            let after = i < types.len() && types[i] == EN;
+            if before || after {
+                for class in &mut types[start..i] {
+                    *class = EN;
+                }
+            }
+        } else {
+            i += 1;
+        }
+    }
+    // W6
+    for class in types.iter_mut() {
+        if *class == ES || *class == ET || *class == CS {
+            *class = ON;
+        }
+    }
+    // W7: European numbers after left-to-right text are left-to-right
+    let mut last_strong = sos;
+    for class in types.iter_mut() {
+        match *class {
+            L | R => last_strong = *class,
+            EN if last_strong == L => *class = L,
+            _ => (),
+        }
+    }
+    // N1, N2: neutrals take the direction of the surrounding text if it agrees,
+    // otherwise the embedding direction
+    let strong_direction = |class: BidiClass| match class {
+        L => L,
+        _ => R,
+    };
+    let mut i = 0;
+    while i < types.len() {
+        if is_neutral(types[i]) {
+            let start = i;
+            while i < types.len() && is_neutral(types[i]) {
+                i += 1;
+            }
+            let before = if start == 0 { sos } else { strong_direction(types[start - 1]) };
+            let after = if i == types.len() { sos } else { strong_direction(types[i]) };
+            let direction = if before == after { before } else { sos };
+            for class in &mut types[start..i] {
+                *class = direction;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    // I1, I2
+    let mut levels: Vec<Level> = types.iter().map(|&class| {
+        match (!is_rtl_level(base_level), class) {
+            (true, R) => base_level + 1,
+            (true, AN) | (true, EN) => base_level + 2,
+            (false, L) | (false, EN) | (false, AN) => base_level + 1,
+            _ => base_level,
+        }
+    }).collect();
+    // L1: separators, and whitespace before them or at the end of the line, use the base level
+    let mut trailing = true;
+    for i in (0..classes.len()).rev() {
+        match classes[i] {
+            B | S => {
+                levels[i] = base_level;
+                trailing = true;
+            }
+            WS | BN if trailing => levels[i] = base_level,
+            _ => trailing = false,
+        }
+    }
+    levels
+}
+
+/// L2: reverses each sequence of characters at or above each odd level, from the highest level
+fn reorder(levels: &[Level]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let highest = levels.iter().cloned().max().unwrap_or(0);
+    let lowest_odd = levels.iter().cloned().filter(|&level| is_rtl_level(level)).min().unwrap_or(highest + 1);
+    let mut level = highest;
+    while level >= lowest_odd && level > 0 {
+        let mut i = 0;
+        while i < order.len() {
+            if levels[order[i]] >= level {
+                let start = i;
+                while i < order.len() && levels[order[i]] >= level {
+                    i += 1;
+                }
+                order[start..i].reverse();
+            } else {
+                i += 1;
+            }
+        }
+        level -= 1;
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use span::Fonts;
+
+    fn levels(line: &str) -> Vec<Level> {
+        let bidi = BidiLine::new(line);
+        (0..bidi.len()).map(|char_i| bidi.level(char_i)).collect()
+    }
+
+    #[test]
+    fn mixed_levels() {
+        assert_eq!(levels("abc \u{5D0}\u{5D1}\u{5D2} def"), vec![0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(levels("\u{5D0}\u{5D1}\u{5D2} abc"), vec![1, 1, 1, 1, 2, 2, 2]);
+        assert!(BidiLine::new("\u{5D0} abc").is_rtl());
+        assert!(!BidiLine::new("abc \u{5D0}").is_rtl());
+    }
+
+    #[test]
+    fn numbers_in_rtl() {
+        // W2: European digits after an Arabic letter are Arabic numbers
+        assert_eq!(levels("\u{628} 12"), vec![1, 1, 2, 2]);
+        assert_eq!(levels("\u{5D0} 12"), vec![1, 1, 2, 2]);
+        // W7: digits after left-to-right text are left-to-right, so the space between stays with them
+        assert_eq!(levels("\u{5D0} abc 12"), vec![1, 1, 2, 2, 2, 2, 2, 2]);
+        assert_eq!(BidiLine::new("\u{628} 12").visual_order(), &[2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn reorder_runs() {
+        assert_eq!(BidiLine::new("\u{5D0}\u{5D1}\u{5D2}").visual_order(), &[2, 1, 0]);
+        assert_eq!(BidiLine::new("abc \u{5D0}\u{5D1}\u{5D2} def").visual_order(),
+                   &[0, 1, 2, 3, 6, 5, 4, 7, 8, 9, 10]);
+        // L2: the left-to-right run at level 2 is reversed twice, keeping its order
+        assert_eq!(BidiLine::new("\u{5D0}\u{5D1}\u{5D2} abc").visual_order(), &[4, 5, 6, 3, 2, 1, 0]);
+        assert_eq!(BidiLine::new("abc").visual_order(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn mirrored_brackets() {
+        assert_eq!(mirror('('), ')');
+        assert_eq!(mirror('\u{AB}'), '\u{BB}');
+        assert_eq!(mirror('a'), 'a');
+
+        let font = ::test_font();
+        let line = "\u{5D0}(\u{5D1})";
+        let bidi = BidiLine::new(line);
+        let glyphs = layout_line(line, &bidi, Fonts::Single(&font, 20.0), 0, 0.0);
+        let chars: Vec<usize> = glyphs.iter().map(|glyph| glyph.char_i).collect();
+        assert_eq!(chars, vec![3, 2, 1, 0]);
+        // the closing bracket is displayed first, as an opening bracket
+        assert_eq!(glyphs[0].glyph.id(), font.glyph('(').unwrap().id());
+        assert_eq!(glyphs[2].glyph.id(), font.glyph(')').unwrap().id());
+    }
+
+    #[test]
+    fn visual_cursors() {
+        let bidi = BidiLine::new("abc \u{5D0}\u{5D1}\u{5D2}");
+        let visual: Vec<usize> = (0..bidi.len() + 1).map(|cursor| bidi.logical_to_visual(cursor)).collect();
+        assert_eq!(visual, vec![0, 1, 2, 3, 7, 6, 5, 4]);
+        for cursor in 0..bidi.len() + 1 {
+            assert_eq!(bidi.visual_to_logical(bidi.logical_to_visual(cursor)), cursor);
+        }
+        assert_eq!(BidiLine::new("").logical_to_visual(0), 0);
+    }
+}
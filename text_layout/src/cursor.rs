@@ -2,10 +2,11 @@
 
 use std;
 use types::{Range, Align, Rect, RectExt, Point};
-use rusttype;
-use rusttype::LayoutIter;
 
-use super::line::{LineRects, LineInfo};
+use super::bidi::{self, BidiLine};
+use super::line::{self, LineRects, LineInfo};
+use super::segment;
+use super::span::Fonts;
 use super::Font;
 
 /// Every possible cursor position within each line of text yielded by the given iterator.
@@ -18,6 +19,7 @@ pub struct XysPerLine<'a, I> {
     font: &'a Font,
     text: &'a str,
     font_size: f32,
+    align: Align,
 }
 
 /// Similarly to `XysPerLine`, yields every possible cursor position within each line of text
@@ -37,12 +39,13 @@ pub struct XysPerLineFromText<'a> {
         >,
     }
 
-/// Each possible cursor position along the *x* axis within a line of text.
+/// Each possible cursor position along the *x* axis within a line of text, in logical order.
+///
+/// In bidirectional text the positions are not in order along the *x* axis, see `bidi`.
 ///
 /// `Xs` iterators are produced by the `XysPerLine` iterator.
-pub struct Xs<'a, 'b> {
-    next_x: Option<f32>,
-    layout: LayoutIter<'a, 'b>,
+pub struct Xs {
+    xs: std::vec::IntoIter<f32>,
 }
 
 /// An index representing the position of a cursor within some text.
@@ -59,59 +62,46 @@ pub struct Index {
 
 
 impl Index {
-    /// The cursor index of the beginning of the word (block of non-whitespace) before `self`.
+    /// The cursor index of the start of the word before `self`, with words found per UAX #29,
+    /// see `segment::word_boundaries`.
     ///
     /// If `self` is at the beginning of the line, call previous, which returns the last
     /// index position of the previous line, or None if it's the first line
     ///
-    /// If `self` points to whitespace, skip past that whitespace, then return the index of
-    /// the start of the word that precedes the whitespace
-    ///
-    /// If `self` is in the middle or end of a word, return the index of the start of that word
+    /// Whitespace and punctuation between `self` and the word are skipped, so if `self` is in
+    /// the middle or end of a word, this returns the index of the start of that word
     pub fn previous_word_start<I>(self, text: &str, mut line_infos: I) -> Option<Self>
         where I: Iterator<Item = LineInfo>
     {
         let Index { line, char } = self;
         if char > 0 {
-            line_infos.nth(line).and_then(|line_info| {
-                let line_count = line_info.char_range().count();
-                let mut chars_rev = (&text[line_info.byte_range()]).chars().rev();
-                if char != line_count {
-                    chars_rev.nth(line_count - char - 1);
-                }
-                let mut new_char = 0;
-                let mut hit_non_whitespace = false;
-                for (i, char_) in chars_rev.enumerate() {
-                    // loop until word starts, then continue until the word ends
-                    if !char_.is_whitespace() {
-                        hit_non_whitespace = true;
-                    }
-                    if char_.is_whitespace() && hit_non_whitespace {
-                        new_char = char - i;
-                        break;
-                    }
-                }
-                Some(Index {
+            line_infos.nth(line).map(|line_info| {
+                let line_text = &text[line_info.byte_range()];
+                let byte = byte_of_char(line_text, char);
+                let boundaries = segment::word_boundaries(line_text);
+                let word_start = boundaries.windows(2).rev()
+                    .find(|bounds| bounds[0] < byte && segment::is_word(&line_text[bounds[0]..bounds[1]]))
+                    .map_or(0, |bounds| bounds[0]);
+                Index {
                     line: line,
-                    char: new_char,
-                })
+                    char: char_of_byte(line_text, word_start),
+                }
             })
         } else {
-            self.previous(line_infos)
+            self.previous(text, line_infos)
         }
     }
 
-    /// The cursor index of the end of the first word (block of non-whitespace) after `self`.
+    /// The cursor index of the end of the first word after `self`, with words found per
+    /// UAX #29, see `segment::word_boundaries`.
     ///
     /// If `self` is at the end of the text, this returns `None`.
     ///
     /// If `self` is at the end of a line other than the last, this returns the first index of
     /// the next line.
     ///
-    /// If `self` points to whitespace, skip past that whitespace, then return the index of
-    /// the end of the word after the whitespace
-    ///
-    /// If `self` is in the middle or start of a word, return the index of the end of that word
+    /// Whitespace and punctuation between `self` and the word are skipped, so if `self` is in
+    /// the middle or start of a word, this returns the index of the end of that word
     pub fn next_word_end<I>(self, text: &str, mut line_infos: I) -> Option<Self>
         where I: Iterator<Item = LineInfo>
     {
@@ -120,25 +110,15 @@ impl Index {
             .and_then(|line_info| {
                 let line_count = line_info.char_range().count();
                 if char < line_count {
-                    let mut chars = (&text[line_info.byte_range()]).chars();
-                    let mut new_char = line_count;
-                    let mut hit_non_whitespace = false;
-                    if char != 0 {
-                        chars.nth(char - 1);
-                    }
-                    for (i, char_) in chars.enumerate() {
-                        // loop until word starts, then continue until the word ends
-                        if !char_.is_whitespace() {
-                            hit_non_whitespace = true;
-                        }
-                        if char_.is_whitespace() && hit_non_whitespace {
-                            new_char = char + i;
-                            break;
-                        }
-                    }
+                    let line_text = &text[line_info.byte_range()];
+                    let byte = byte_of_char(line_text, char);
+                    let boundaries = segment::word_boundaries(line_text);
+                    let word_end = boundaries.windows(2)
+                        .find(|bounds| bounds[1] > byte && segment::is_word(&line_text[bounds[0]..bounds[1]]))
+                        .map_or(line_text.len(), |bounds| bounds[1]);
                     Some(Index {
                         line: line,
-                        char: new_char,
+                        char: char_of_byte(line_text, word_end),
                     })
                 } else {
                     line_infos.next().map(|_| {
@@ -159,18 +139,20 @@ impl Index {
     /// index position of the previous line.
     ///
     /// If `self` is a position other than the start of a line, it will return the position
-    /// that is immediately to the left.
-    pub fn previous<I>(self, mut line_infos: I) -> Option<Self>
+    /// at the start of the grapheme cluster before it, so that combining marks, emoji
+    /// sequences and flags are stepped over as a whole.
+    pub fn previous<I>(self, text: &str, mut line_infos: I) -> Option<Self>
         where I: Iterator<Item = LineInfo>
     {
         let Index { line, char } = self;
         if char > 0 {
-            let new_char = char - 1;
             line_infos.nth(line)
-                .and_then(|info| if new_char <= info.char_range().count() {
+                .and_then(|info| if char <= info.char_range().count() {
+                    let line_text = &text[info.byte_range()];
+                    let byte = segment::previous_grapheme_boundary(line_text, byte_of_char(line_text, char));
                     Some(Index {
                         line: line,
-                        char: new_char,
+                        char: char_of_byte(line_text, byte),
                     })
                 } else {
                     None
@@ -197,9 +179,9 @@ impl Index {
     /// If `self` is at the end of a line other than the last, this returns the first index of
     /// the next line.
     ///
-    /// If `self` is a position other than the end of a line, it will return the position that
-    /// is immediately to the right.
-    pub fn next<I>(self, mut line_infos: I) -> Option<Self>
+    /// If `self` is a position other than the end of a line, it will return the position at
+    /// the end of the grapheme cluster after it.
+    pub fn next<I>(self, text: &str, mut line_infos: I) -> Option<Self>
         where I: Iterator<Item = LineInfo>
     {
         let Index { line, char } = self;
@@ -212,13 +194,41 @@ impl Index {
                     }
                 })
             } else {
+                let line_text = &text[info.byte_range()];
+                let byte = segment::next_grapheme_boundary(line_text, byte_of_char(line_text, char));
                 Some(Index {
                     line: line,
-                    char: char + 1,
+                    char: char_of_byte(line_text, byte),
                 })
             })
     }
 
+    /// The visual position of `self` within its line, counting cursor positions from the left.
+    ///
+    /// Returns `None` if `self.line` is out of bounds.
+    pub fn to_visual<I>(self, text: &str, mut line_infos: I) -> Option<usize>
+        where I: Iterator<Item = LineInfo>
+    {
+        line_infos.nth(self.line).map(|info| {
+            BidiLine::new(&text[info.byte_range()]).logical_to_visual(self.char)
+        })
+    }
+
+    /// The cursor index at the visual position `visual` on `line`, counting from the left.
+    ///
+    /// Returns `None` if `line` is out of bounds.
+    pub fn from_visual<I>(line: usize, visual: usize, text: &str, mut line_infos: I) -> Option<Self>
+        where I: Iterator<Item = LineInfo>
+    {
+        line_infos.nth(line).map(|info| {
+            let bidi = BidiLine::new(&text[info.byte_range()]);
+            Index {
+                line: line,
+                char: bidi.visual_to_logical(std::cmp::min(visual, bidi.len())),
+            }
+        })
+    }
+
     /// Clamps `self` to the given lines.
     ///
     /// If `self` would lie after the end of the last line, return the index at the end of the
@@ -253,6 +263,16 @@ impl Index {
 }
 
 
+/// The byte index of the char at `char` within `line`, or the end of the line
+fn byte_of_char(line: &str, char: usize) -> usize {
+    line.char_indices().nth(char).map_or(line.len(), |(byte_i, _)| byte_i)
+}
+
+/// The number of chars in `line` before the byte index `byte`
+fn char_of_byte(line: &str, byte: usize) -> usize {
+    line[..byte].chars().count()
+}
+
 /// Every possible cursor position within each line of text yielded by the given iterator.
 ///
 /// Yields `(xs, y_range)`, where `y_range` is the `Range` occupied by the line across the *y*
@@ -260,13 +280,15 @@ impl Index {
 pub fn xys_per_line<'a, I>(lines_with_rects: I,
                            font: &'a Font,
                            text: &'a str,
-                           font_size: f32)
+                           font_size: f32,
+                           align: Align)
                            -> XysPerLine<'a, I> {
     XysPerLine {
         lines_with_rects: lines_with_rects,
         font: font,
         text: text,
         font_size: font_size,
+        align: align,
     }
 }
 
@@ -296,7 +318,7 @@ pub fn xys_per_line_from_text<'a>(text: &'a str,
     let lines = line_infos.clone();
     let lines_with_rects = lines.zip(line_rects.clone());
     XysPerLineFromText {
-        xys_per_line: super::cursor::xys_per_line(lines_with_rects, font, text, font_size),
+        xys_per_line: super::cursor::xys_per_line(lines_with_rects, font, text, font_size, align),
     }
 }
 
@@ -318,8 +340,8 @@ pub fn index_before_char<I>(line_infos: I, char_index: usize) -> Option<Index>
 }
 
 /// Determine the *xy* location of the cursor at the given cursor `Index`.
-pub fn xy_at<'a, I>(xys_per_line: I, idx: Index) -> Option<(f32, Range)>
-    where I: Iterator<Item = (Xs<'a, 'a>, Range)>
+pub fn xy_at<I>(xys_per_line: I, idx: Index) -> Option<(f32, Range)>
+    where I: Iterator<Item = (Xs, Range)>
 {
     for (i, (xs, y)) in xys_per_line.enumerate() {
         if i == idx.line {
@@ -337,8 +359,8 @@ pub fn xy_at<'a, I>(xys_per_line: I, idx: Index) -> Option<(f32, Range)>
 /// return the line index, Xs iterator, and y-range of that line
 ///
 /// Returns `None` if there are no lines
-pub fn closest_line<'a, I>(y_pos: f32, xys_per_line: I) -> Option<(usize, Xs<'a, 'a>, Range)>
-    where I: Iterator<Item = (Xs<'a, 'a>, Range)>
+pub fn closest_line<I>(y_pos: f32, xys_per_line: I) -> Option<(usize, Xs, Range)>
+    where I: Iterator<Item = (Xs, Range)>
 {
     let mut xys_per_line_enumerated = xys_per_line.enumerate();
     xys_per_line_enumerated.next().and_then(|(first_line_idx, (first_line_xs, first_line_y))| {
@@ -366,12 +388,12 @@ pub fn closest_line<'a, I>(y_pos: f32, xys_per_line: I) -> Option<(usize, Xs<'a,
 /// cursor.
 ///
 /// Returns `None` if the given `text` is empty.
-pub fn closest_cursor_index_and_xy<'a, I>(point: Point, xys_per_line: I) -> Option<(Index, Point)>
-    where I: Iterator<Item = (Xs<'a, 'a>, Range)>
+pub fn closest_cursor_index_and_xy<I>(point: Point, xys_per_line: I) -> Option<(Index, Point)>
+    where I: Iterator<Item = (Xs, Range)>
 {
-    closest_line(point.x, xys_per_line)
+    closest_line(point.y, xys_per_line)
         .and_then(|(closest_line_idx, closest_line_xs, closest_line_y)| {
-            let (closest_char_idx, closest_x) = closest_cursor_index_on_line(point.y,
+            let (closest_char_idx, closest_x) = closest_cursor_index_on_line(point.x,
                                                                              closest_line_xs);
             let index = Index {
                 line: closest_line_idx,
@@ -384,20 +406,19 @@ pub fn closest_cursor_index_and_xy<'a, I>(point: Point, xys_per_line: I) -> Opti
 
 /// Find the closest cursor index to the given `x` position on the given line along with the
 /// `x` position of that cursor.
-pub fn closest_cursor_index_on_line<'a>(x_pos: f32, line_xs: Xs<'a, 'a>) -> (usize, f32) {
+pub fn closest_cursor_index_on_line(x_pos: f32, line_xs: Xs) -> (usize, f32) {
     let mut xs_enumerated = line_xs.enumerate();
     // `xs` always yields at least one `x` (the start of the line).
     let (first_idx, first_x) = xs_enumerated.next().unwrap();
     let first_diff = (x_pos - first_x).abs();
     let mut closest = (first_idx, first_x);
     let mut closest_diff = first_diff;
+    // positions are in logical order, which is only left to right in left-to-right text
     for (i, x) in xs_enumerated {
         let diff = (x_pos - x).abs();
         if diff < closest_diff {
             closest = (i, x);
             closest_diff = diff;
-        } else {
-            break;
         }
     }
     closest
@@ -409,45 +430,87 @@ impl<'a, I> Iterator for XysPerLine<'a, I>
 {
     // The `Range` occupied by the line across the *y* axis, along with an iterator yielding
     // each possible cursor position along the *x* axis.
-    type Item = (Xs<'a, 'a>, Range);
+    type Item = (Xs, Range);
     fn next(&mut self) -> Option<Self::Item> {
-        let XysPerLine { ref mut lines_with_rects, font, text, font_size } = *self;
-        let scale = super::pt_to_scale(font_size);
+        let XysPerLine { ref mut lines_with_rects, font, text, font_size, align } = *self;
         lines_with_rects.next().map(|(line_info, line_rect)| {
             let line = &text[line_info.byte_range()];
-            let (x, y) = (line_rect.left(), line_rect.top());
-            let point = rusttype::Point { x: x, y: y };
-            let y = line_rect.y_range();
-            let layout = font.layout(line, scale, point);
-            let xs = Xs {
-                next_x: Some(line_rect.left()),
-                layout: layout,
+            let word_spacing = match align {
+                Align::Justify => line::justified_word_spacing(line, &line_info, line_rect.width()),
+                _ => 0.0,
             };
-            (xs, y)
+            let bidi = BidiLine::new(line);
+            let glyphs = bidi::layout_line(line, &bidi, Fonts::Single(font, font_size), 0, word_spacing);
+            // the cursor at each visual position is at the start of the glyph to its right, with
+            // kerning and word spacing applied, or after the last glyph at the end of the line
+            let end = glyphs.last().map_or(0.0, |g| g.x + g.glyph.h_metrics().advance_width);
+            let visual_xs: Vec<f32> = Some(0.0).into_iter()
+                .chain(glyphs.iter().skip(1).map(|g| g.x))
+                .chain(if glyphs.is_empty() { None } else { Some(end) })
+                .map(|x| line_rect.left() + x)
+                .collect();
+            let xs: Vec<f32> = (0..bidi.len() + 1)
+                .map(|cursor| visual_xs[bidi.logical_to_visual(cursor)])
+                .collect();
+            (Xs { xs: xs.into_iter() }, line_rect.y_range())
         })
     }
 }
 
 impl<'a> Iterator for XysPerLineFromText<'a> {
-    type Item = (Xs<'a, 'a>, Range);
+    type Item = (Xs, Range);
     fn next(&mut self) -> Option<Self::Item> {
         self.xys_per_line.next()
     }
 }
 
-impl<'a, 'b> Iterator for Xs<'a, 'b> {
+impl Iterator for Xs {
     // Each possible cursor position along the *x* axis.
     type Item = f32;
     fn next(&mut self) -> Option<Self::Item> {
-        self.next_x.map(|x| {
-            self.next_x = self.layout
-                .next()
-                .map(|g| {
-                    g.pixel_bounding_box()
-                        .map(|r| r.max.x as f32)
-                        .unwrap_or_else(|| x + g.unpositioned().h_metrics().advance_width as f32)
-                });
-            x
-        })
+        self.xs.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use line::LineInfos;
+    use Wrap;
+    use types::Size;
+
+    fn line_infos(text: &str, font: &Font) -> Vec<LineInfo> {
+        LineInfos::new(text, font, 20.0, Wrap::Whitespace, 1000.0).collect()
+    }
+
+    #[test]
+    fn visual_round_trip() {
+        let font = ::test_font();
+        let text = "abc \u{5D0}\u{5D1}\u{5D2}\nxy";
+        let infos = line_infos(text, &font);
+        for &(line, len) in &[(0, 7), (1, 2)] {
+            for char in 0..len + 1 {
+                let index = Index { line: line, char: char };
+                let visual = index.to_visual(text, infos.iter().cloned()).unwrap();
+                assert_eq!(Index::from_visual(line, visual, text, infos.iter().cloned()), Some(index));
+            }
+        }
+        // the cursor before the first hebrew letter is at the right of the line
+        assert_eq!(Index { line: 0, char: 4 }.to_visual(text, infos.iter().cloned()), Some(7));
+        assert_eq!(Index { line: 2, char: 0 }.to_visual(text, infos.iter().cloned()), None);
+    }
+
+    #[test]
+    fn rtl_cursor_xs() {
+        let font = ::test_font();
+        let text = "\u{5D0}\u{5D1}\u{5D2}";
+        let infos = line_infos(text, &font);
+        let rect = Rect::new(Point::new(0.0, 0.0), Size::new(1000.0, 100.0));
+        let (xs, _) = xys_per_line_from_text(text, &infos, &font, 20.0, Align::Start, 0.0, rect).next().unwrap();
+        let xs: Vec<f32> = xs.collect();
+        // cursor positions are in logical order, so they run from right to left
+        assert_eq!(xs.len(), 4);
+        assert_eq!(xs[3], 0.0);
+        assert!(xs.windows(2).all(|pair| pair[0] > pair[1]));
     }
 }
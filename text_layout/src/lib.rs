@@ -7,10 +7,16 @@ pub mod types;
 pub mod cursor;
 pub mod glyph;
 pub mod line;
+pub mod line_break;
+pub mod span;
+pub mod bidi;
+pub mod segment;
 
 use std::f32;
 use rusttype::Scale;
+use self::bidi::BidiLine;
 use self::line::{LineRects, LineInfo, LineInfos};
+use self::span::Fonts;
 use self::types::*;
 
 
@@ -20,6 +26,12 @@ pub type PositionedGlyph = rusttype::PositionedGlyph<'static>;
 
 pub type Font = rusttype::Font<'static>;
 
+#[cfg(test)]
+fn test_font() -> Font {
+    let data: &'static [u8] = include_bytes!("../../assets/fonts/NotoSans/NotoSans-Regular.ttf");
+    rusttype::FontCollection::from_bytes(data).into_font().unwrap()
+}
+
 pub use types::Align;
 
 /// The way in which text should wrap around the width.
@@ -30,6 +42,23 @@ pub enum Wrap {
     Character,
     /// Wrap at the first word that exceeds the width.
     Whitespace,
+    /// Wrap at the last line break opportunity before the width is exceeded, following the
+    /// Unicode line breaking algorithm, see `line_break`. Lines can also break at soft hyphens.
+    Unicode,
+}
+
+/// How text that doesn't fit within its bounds is shown, see `span::overflow_text`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Overflow {
+    /// Text outside the bounds is cut off.
+    Clip,
+    /// The end of the last line that fits is replaced with an ellipsis.
+    EllipsisEnd,
+    /// The middle of the first line is replaced with an ellipsis, and later lines are dropped.
+    EllipsisMiddle,
+    /// At most this many lines are shown, with an ellipsis at the end of the last one
+    /// if there are more.
+    MaxLines(usize),
 }
 
 pub fn get_text_size(text: &str,
@@ -81,31 +110,27 @@ pub fn get_positioned_glyphs(text: &str,
 {
     let line_infos: Vec<LineInfo> = LineInfos::new(text, font, font_size, line_wrap, rect.width())
         .collect();
-    let line_infos = line_infos.iter().cloned();
-    let line_texts = line_infos.clone().map(|info| &text[info.byte_range()]);
-    let line_rects = LineRects::new(line_infos, font_size, rect, align, line_height);
-    let scale = Scale::uniform(font_size);
+    let line_rects = LineRects::new(line_infos.iter().cloned(), font_size, rect, align, line_height);
 
     let mut positioned_glyphs = Vec::new();
-    for (line_text, line_rect) in line_texts.zip(line_rects) {
+    for (line_info, line_rect) in line_infos.iter().zip(line_rects) {
+        let line_text = &text[line_info.byte_range()];
         // point specifies bottom left corner of text line
         let point = rusttype::Point {
             x: line_rect.left(),
             y: line_rect.top() + font_size,
         };
+        let word_spacing = match align {
+            Align::Justify => line::justified_word_spacing(line_text, line_info, line_rect.width()),
+            _ => 0.0,
+        };
 
-        positioned_glyphs.extend(font.glyphs_for(line_text.chars())
-            .scan((None, 0.0), |state, g| {
-                let &mut (last, x) = state;
-                let g = g.scaled(scale);
-
-                let kern = last.map(|last| font.pair_kerning(scale, last, g.id())).unwrap_or(0.0);
-                let width = g.h_metrics().advance_width;
-
-                let next = g.positioned(point + rusttype::vector(x, 0.0));
-                *state = (Some(next.id()), x + width + kern);
-                Some(next.standalone())
-            }));
+        // glyphs are positioned in display order, so right-to-left runs are reversed
+        let bidi = BidiLine::new(line_text);
+        let glyphs = bidi::layout_line(line_text, &bidi, Fonts::Single(font, font_size), 0, word_spacing);
+        positioned_glyphs.extend(glyphs.into_iter().map(|g| {
+            g.glyph.positioned(point + rusttype::vector(g.x, 0.0)).standalone()
+        }));
     }
     positioned_glyphs
 }
@@ -2,7 +2,6 @@
 /// Text handling logic related to individual lines of text.
 ///
 /// This module is the core of multi-line text handling.
-use rusttype;
 use super::Font;
 use rusttype::Scale;
 use types::{Range, Align, Rect, RectExt};
@@ -12,6 +11,9 @@ use std::str::CharIndices;
 use std::iter::Peekable;
 use super::Wrap;
 use super::glyph::SelectedGlyphRectsPerLine;
+use super::line_break::{self, SOFT_HYPHEN};
+use super::segment;
+use super::span::{Span, Fonts};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 enum BreakType {
@@ -85,6 +87,40 @@ impl LineInfo {
     pub fn char_range(self) -> std::ops::Range<usize> {
         self.start_char..self.end_char()
     }
+
+    /// Whether the line is the last of its paragraph, ending at a newline or the end of the text
+    /// rather than being wrapped.
+    pub fn ends_paragraph(&self) -> bool {
+        match self.end_break.break_type {
+            BreakType::Wrap { .. } => false,
+            _ => true,
+        }
+    }
+}
+
+/// The *x* `Range` of the line within `bounding_x` for the given alignment.
+///
+/// Lines justified by `Align::Justify` take the whole width, see `justified_word_spacing`.
+pub fn align_line(line_info: &LineInfo, align: Align, bounding_x: Range) -> Range {
+    let range = Range::new(0.0, line_info.width);
+    match align {
+        Align::Start => range.align_start_of(bounding_x),
+        Align::Middle => range.align_middle_of(bounding_x),
+        Align::End => range.align_end_of(bounding_x),
+        Align::Justify if line_info.ends_paragraph() => range.align_start_of(bounding_x),
+        Align::Justify => bounding_x,
+    }
+}
+
+/// The space added after each space between words of the line `text`, so that the line fills
+/// `width` when justified. Lines that end a paragraph aren't justified.
+pub fn justified_word_spacing(text: &str, line_info: &LineInfo, width: f32) -> f32 {
+    let spaces = text.trim().chars().filter(|ch| ch.is_whitespace()).count();
+    if line_info.ends_paragraph() || spaces == 0 || line_info.width >= width {
+        0.0
+    } else {
+        (width - line_info.width) / spaces as f32
+    }
 }
 
 /// An iterator yielding an `Info` struct for each line in the given `text` wrapped by the
@@ -98,8 +134,7 @@ impl LineInfo {
 #[derive(Copy, Clone)]
 pub struct LineInfos<'a> {
     text: &'a str,
-    font: &'a Font,
-    font_size: f32,
+    fonts: Fonts<'a>,
     max_width: f32,
     line_wrap: Wrap,
     /// The index that indicates the start of the next line to be yielded.
@@ -117,10 +152,20 @@ impl<'a> LineInfos<'a> {
                line_wrap: Wrap,
                max_width: f32)
                -> Self {
+        LineInfos::with_fonts(text, Fonts::Single(font, font_size), line_wrap, max_width)
+    }
+    /// Lines of text where each span is measured with its own font and size
+    pub fn with_spans(text: &'a str,
+                      spans: &'a [Span<'a>],
+                      line_wrap: Wrap,
+                      max_width: f32)
+                      -> Self {
+        LineInfos::with_fonts(text, Fonts::Spans(spans), line_wrap, max_width)
+    }
+    fn with_fonts(text: &'a str, fonts: Fonts<'a>, line_wrap: Wrap, max_width: f32) -> Self {
         LineInfos {
             text: text,
-            font: font,
-            font_size: font_size,
+            fonts: fonts,
             max_width: max_width,
             line_wrap: line_wrap,
             start_byte: 0,
@@ -134,8 +179,7 @@ impl<'a> Iterator for LineInfos<'a> {
     type Item = LineInfo;
     fn next(&mut self) -> Option<Self::Item> {
         let LineInfos { text,
-                        font,
-                        font_size,
+                        fonts,
                         max_width,
                         line_wrap,
                         ref mut start_byte,
@@ -144,9 +188,10 @@ impl<'a> Iterator for LineInfos<'a> {
 
         let text_line = &text[*start_byte..];
         let (next, width) = match line_wrap {
-            Wrap::NoWrap => next_break(text_line, font, font_size),
-            Wrap::Character => next_break_by_character(text_line, font, font_size, max_width),
-            Wrap::Whitespace => next_break_by_whitespace(text_line, font, font_size, max_width),
+            Wrap::NoWrap => next_break(text_line, Advances::new(fonts, *start_byte)),
+            Wrap::Character => next_break_by_character(text_line, Advances::new(fonts, *start_byte), max_width),
+            Wrap::Whitespace => next_break_by_whitespace(text_line, Advances::new(fonts, *start_byte), max_width),
+            Wrap::Unicode => next_break_by_unicode(text_line, Advances::new(fonts, *start_byte), max_width),
         };
         match next.break_type {
             BreakType::Newline { len_bytes } |
@@ -164,7 +209,10 @@ impl<'a> Iterator for LineInfos<'a> {
                         width: width,
                     };
                     *start_byte = info.start_byte + next.byte + len_bytes;
-                    *start_char = info.start_char + next.char + 1;
+                    *start_char = info.start_char + next.char + match next.break_type {
+                        BreakType::Wrap { .. } => text_line[next.byte..next.byte + len_bytes].chars().count(),
+                        _ => 1,
+                    };
                     *last_break = Some(next_break);
                     Some(info)
                 }
@@ -212,6 +260,7 @@ pub struct LineRects<I> {
     infos: I,
     align: Align,
     line_height: f32,
+    bounding_x: Range,
     next: Option<Rect>,
 }
 
@@ -229,16 +278,11 @@ impl<I> LineRects<I>
                line_height: f32)
                -> Self {
         let num_lines = infos.len();
+        let bounding_x = bounding_rect.x_range();
         let first_rect = infos.next().map(|first_info| {
-            let bounding_x = bounding_rect.x_range();
             let bounding_y = bounding_rect.y_range();
             // Calculate the `x` `Range` of the first line `Rect`.
-            let range = Range::new(0.0, first_info.width);
-            let x = match align {
-                Align::Start => range.align_start_of(bounding_x),
-                Align::Middle => range.align_middle_of(bounding_x),
-                Align::End => range.align_end_of(bounding_x),
-            };
+            let x = align_line(&first_info, align, bounding_x);
 
             // Calculate the `y` `Range` of the first line `Rect`.
             let total_text_height = num_lines as f32 * line_height;
@@ -255,6 +299,7 @@ impl<I> LineRects<I>
             next: first_rect,
             align: align,
             line_height: line_height,
+            bounding_x: bounding_x,
         }
     }
 }
@@ -264,18 +309,11 @@ impl<I> Iterator for LineRects<I>
 {
     type Item = Rect;
     fn next(&mut self) -> Option<Self::Item> {
-        let LineRects { ref mut next, ref mut infos, align, line_height } = *self;
+        let LineRects { ref mut next, ref mut infos, align, line_height, bounding_x } = *self;
         next.map(|line_rect| {
             *next = infos.next().map(|info| {
                 let y = Range::new(line_rect.bottom(), line_rect.bottom() + line_height);
-                let x = {
-                    let range = Range::new(0.0, info.width);
-                    match align {
-                        Align::Start => range.align_start_of(line_rect.x_range()),
-                        Align::Middle => range.align_middle_of(line_rect.x_range()),
-                        Align::End => range.align_end_of(line_rect.x_range()),
-                    }
-                };
+                let x = align_line(&info, align, bounding_x);
                 Rect::from_ranges(x, y)
             });
 
@@ -352,6 +390,45 @@ fn advance_width(ch: char, font: &Font, scale: Scale, last_glyph: &mut Option<Gl
     (kern + advance_width) as f32
 }
 
+/// Advance widths of successive characters in a line, where each character may be in a
+/// different span. Kerning is only applied between glyphs of the same span.
+struct Advances<'a> {
+    fonts: Fonts<'a>,
+    /// Byte index of the start of the line within the whole text
+    offset: usize,
+    last_span: Option<usize>,
+    last_glyph: Option<GlyphId>,
+}
+
+impl<'a> Advances<'a> {
+    fn new(fonts: Fonts<'a>, offset: usize) -> Self {
+        Advances {
+            fonts: fonts,
+            offset: offset,
+            last_span: None,
+            last_glyph: None,
+        }
+    }
+    fn next(&mut self, byte_i: usize, ch: char) -> f32 {
+        let (span, font, font_size) = self.fonts.at(self.offset + byte_i);
+        if self.last_span != Some(span) {
+            self.last_span = Some(span);
+            self.last_glyph = None;
+        }
+        if ch == SOFT_HYPHEN {
+            // invisible unless the line breaks after it, see `hyphen`
+            self.last_glyph = None;
+            return 0.0;
+        }
+        advance_width(ch, font, super::pt_to_scale(font_size), &mut self.last_glyph)
+    }
+    /// The width of the hyphen shown when a line breaks at the soft hyphen at `byte_i`
+    fn hyphen(&self, byte_i: usize) -> f32 {
+        let (_, font, font_size) = self.fonts.at(self.offset + byte_i);
+        font.glyph('-').unwrap().scaled(super::pt_to_scale(font_size)).h_metrics().advance_width
+    }
+}
+
 fn peek_next_char(char_indices: &mut Peekable<CharIndices>, next_char_expected: char) -> bool {
     if let Some(&(_, next_char)) = char_indices.peek() {
         next_char == next_char_expected
@@ -362,12 +439,10 @@ fn peek_next_char(char_indices: &mut Peekable<CharIndices>, next_char_expected:
 
 /// Returns the next index at which the text naturally breaks via a newline character,
 /// along with the width of the line.
-fn next_break(text: &str, font: &Font, font_size: f32) -> (Break, f32) {
-    let scale = super::pt_to_scale(font_size);
+fn next_break(text: &str, mut advances: Advances) -> (Break, f32) {
     let mut width = 0.0;
     let mut char_i = 0;
     let mut char_indices = text.char_indices().peekable();
-    let mut last_glyph = None;
     while let Some((byte_i, ch)) = char_indices.next() {
         // Check for a newline.
         if ch == '\r' && peek_next_char(&mut char_indices, '\n') {
@@ -379,7 +454,7 @@ fn next_break(text: &str, font: &Font, font_size: f32) -> (Break, f32) {
         }
 
         // Update the width.
-        width += advance_width(ch, font, scale, &mut last_glyph);
+        width += advances.next(byte_i, ch);
         char_i += 1;
     }
     let break_ = Break::new(text.len(), char_i, BreakType::End);
@@ -391,15 +466,12 @@ fn next_break(text: &str, font: &Font, font_size: f32) -> (Break, f32) {
 ///
 /// Also returns the width of each line alongside the Break.
 fn next_break_by_character(text: &str,
-                           font: &Font,
-                           font_size: f32,
+                           mut advances: Advances,
                            max_width: f32)
                            -> (Break, f32) {
-    let scale = super::pt_to_scale(font_size);
     let mut width = 0.0;
     let mut char_i = 0;
     let mut char_indices = text.char_indices().peekable();
-    let mut last_glyph = None;
     while let Some((byte_i, ch)) = char_indices.next() {
         // Check for a newline.
         if ch == '\r' && peek_next_char(&mut char_indices, '\n') {
@@ -411,7 +483,7 @@ fn next_break_by_character(text: &str,
         }
 
         // Add the character's width to the width so far.
-        let new_width = width + advance_width(ch, font, scale, &mut last_glyph);
+        let new_width = width + advances.next(byte_i, ch);
 
         // Check for a line wrap.
         if new_width > max_width {
@@ -436,8 +508,7 @@ fn next_break_by_character(text: &str,
 ///
 /// Also returns the width the line alongside the Break.
 fn next_break_by_whitespace(text: &str,
-                            font: &Font,
-                            font_size: f32,
+                            mut advances: Advances,
                             max_width: f32)
                             -> (Break, f32) {
     struct Last {
@@ -445,12 +516,10 @@ fn next_break_by_whitespace(text: &str,
         char: usize,
         width_before: f32,
     }
-    let scale = super::pt_to_scale(font_size);
     let mut last_whitespace_start = None;
     let mut width = 0.0;
     let mut char_i = 0;
     let mut char_indices = text.char_indices().peekable();
-    let mut last_glyph = None;
     while let Some((byte_i, ch)) = char_indices.next() {
 
         // Check for a newline.
@@ -463,7 +532,7 @@ fn next_break_by_whitespace(text: &str,
         }
 
         // Add the character's width to the width so far.
-        let new_width = width + advance_width(ch, font, scale, &mut last_glyph);
+        let new_width = width + advances.next(byte_i, ch);
 
         // Check for a line wrap.
         if new_width > max_width {
@@ -496,17 +565,235 @@ fn next_break_by_whitespace(text: &str,
     (break_, width)
 }
 
+/// Returns the next index at which the text will break by either:
+/// - A newline character.
+/// - A line wrap at the last break opportunity from the Unicode line breaking algorithm before
+/// the first character exceeding the `max_width`. Whitespace before the break is left out of
+/// the line, and a line broken at a soft hyphen includes the width of a hyphen.
+/// - A line wrap at the beginning of the first grapheme cluster exceeding the `max_width`,
+/// if there is no break opportunity within `max_width`.
+///
+/// Also returns the width the line alongside the Break.
+fn next_break_by_unicode(text: &str,
+                         mut advances: Advances,
+                         max_width: f32)
+                         -> (Break, f32) {
+    struct Last {
+        byte: usize,
+        char: usize,
+        width_before: f32,
+        len_bytes: usize,
+    }
+    // break opportunities only depend on the text up to the next newline
+    let paragraph = &text[..text.find('\n').map_or(text.len(), |newline| newline + 1)];
+    let opportunities = line_break::break_opportunities(paragraph);
+    let graphemes = segment::grapheme_boundaries(paragraph);
+    let mut opportunities = opportunities.into_iter().peekable();
+
+    let mut last_opportunity = None;
+    let mut whitespace_start: Option<(usize, usize, f32)> = None;
+    let mut last_char = None;
+    let mut width = 0.0;
+    let mut char_i = 0;
+    let mut char_indices = text.char_indices().peekable();
+    while let Some((byte_i, ch)) = char_indices.next() {
+
+        // Check for a newline.
+        if ch == '\r' && peek_next_char(&mut char_indices, '\n') {
+            let break_ = Break::new(byte_i, char_i, BreakType::Newline { len_bytes: 2 });
+            return (break_, width);
+        } else if ch == '\n' {
+            let break_ = Break::new(byte_i, char_i, BreakType::Newline { len_bytes: 1 });
+            return (break_, width);
+        }
+
+        // Check for a break opportunity before this character.
+        while opportunities.peek().map_or(false, |&opportunity| opportunity < byte_i) {
+            opportunities.next();
+        }
+        if opportunities.peek() == Some(&byte_i) {
+            if last_char == Some(SOFT_HYPHEN) {
+                let width_before = width + advances.hyphen(byte_i);
+                if width_before <= max_width {
+                    last_opportunity = Some(Last {
+                        byte: byte_i,
+                        char: char_i,
+                        width_before: width_before,
+                        len_bytes: 0,
+                    });
+                }
+            } else {
+                let (byte, char, width_before) = whitespace_start.unwrap_or((byte_i, char_i, width));
+                last_opportunity = Some(Last {
+                    byte: byte,
+                    char: char,
+                    width_before: width_before,
+                    len_bytes: byte_i - byte,
+                });
+            }
+        }
+
+        // Add the character's width to the width so far.
+        let new_width = width + advances.next(byte_i, ch);
+
+        // Check for a line wrap, whitespace at the end of a line may exceed the width.
+        if new_width > max_width && !ch.is_whitespace() {
+            if let Some(Last { byte, char, width_before, len_bytes }) = last_opportunity {
+                let break_ = Break::new(byte, char, BreakType::Wrap { len_bytes: len_bytes });
+                return (break_, width_before);
+            }
+            if char_i > 0 && graphemes.contains(&byte_i) {
+                let break_ = Break::new(byte_i, char_i, BreakType::Wrap { len_bytes: 0 });
+                return (break_, width);
+            }
+        }
+
+        if ch.is_whitespace() {
+            whitespace_start = whitespace_start.or(Some((byte_i, char_i, width)));
+        } else {
+            whitespace_start = None;
+        }
+        last_char = Some(ch);
+        width = new_width;
+        char_i += 1;
+    }
+
+    let break_ = Break::new(text.len(), char_i, BreakType::End);
+    (break_, width)
+}
+
+/// The byte index and advance width of each char in the byte `range` of `text`, with kerning
+/// applied the same as in `LineInfo` widths.
+pub(super) fn char_advances(text: &str, fonts: Fonts, range: std::ops::Range<usize>) -> Vec<(usize, f32)> {
+    let mut advances = Advances::new(fonts, range.start);
+    text[range.clone()].char_indices()
+        .map(|(byte_i, ch)| (range.start + byte_i, advances.next(byte_i, ch)))
+        .collect()
+}
+
 /// Produce the width of the given line of text including spaces (i.e. ' ').
+///
+/// This is the sum of the kerned advance widths, the same as the widths of `LineInfo`s.
 pub fn width(text: &str, font: &Font, font_size: f32) -> f32 {
-    let scale = Scale::uniform(font_size as f32);
-    let point = rusttype::Point { x: 0.0, y: 0.0 };
-
-    let mut total_w = 0.0;
-    for g in font.layout(text, scale, point) {
-        match g.pixel_bounding_box() {
-            Some(bb) => total_w = bb.max.x as f32,
-            None => total_w += g.unpositioned().h_metrics().advance_width,
-        }
+    let scale = Scale::uniform(font_size);
+    let mut last_glyph = None;
+    text.chars().map(|ch| advance_width(ch, font, scale, &mut last_glyph)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{Point, Size};
+
+    const FONT_SIZE: f32 = 20.0;
+
+    fn wrapped_lines<'a>(text: &'a str, font: &Font, max_width: f32) -> Vec<&'a str> {
+        LineInfos::new(text, font, FONT_SIZE, Wrap::Unicode, max_width)
+            .map(|info| &text[info.byte_range()])
+            .collect()
+    }
+
+    #[test]
+    fn unicode_wrap_keeps_closing_punctuation() {
+        let font = ::test_font();
+        let max_width = width("Hello world", &font, FONT_SIZE) + 1.0;
+        assert_eq!(wrapped_lines("Hello world.", &font, max_width), vec!["Hello", "world."]);
+        let max_width = width("Hello\u{A0}wor", &font, FONT_SIZE);
+        assert_eq!(wrapped_lines("a Hello\u{A0}world", &font, max_width), vec!["a", "Hello\u{A0}wor", "ld"]);
+    }
+
+    #[test]
+    fn unicode_wrap_at_soft_hyphen() {
+        let font = ::test_font();
+        let text = "hy\u{AD}hy";
+        let hyphen = font.glyph('-').unwrap().scaled(Scale::uniform(FONT_SIZE));
+        let max_width = width("hy-", &font, FONT_SIZE) + 1.0;
+        let infos: Vec<LineInfo> = LineInfos::new(text, &font, FONT_SIZE, Wrap::Unicode, max_width).collect();
+        assert_eq!(infos.len(), 2);
+        assert_eq!(&text[infos[0].byte_range()], "hy\u{AD}");
+        // the line includes the width of the hyphen shown at the break
+        let expected = width("hy", &font, FONT_SIZE) + hyphen.h_metrics().advance_width;
+        assert!((infos[0].width - expected).abs() < 0.01);
+        // and the soft hyphen is drawn as a hyphen
+        let rect = Rect::new(Point::new(0.0, 0.0), Size::new(max_width, 100.0));
+        let glyphs = ::get_positioned_glyphs(text, rect, &font, FONT_SIZE, FONT_SIZE, Wrap::Unicode, Align::Start);
+        assert_eq!(glyphs.len(), 5);
+        assert_eq!(glyphs[2].id(), hyphen.id());
+        // without a break the soft hyphen is invisible
+        let infos: Vec<LineInfo> = LineInfos::new(text, &font, FONT_SIZE, Wrap::Unicode, 1000.0).collect();
+        assert_eq!(infos.len(), 1);
+        assert!((infos[0].width - width("hyhy", &font, FONT_SIZE)).abs() < 1.0);
+    }
+
+    #[test]
+    fn unicode_wrap_falls_back_to_graphemes() {
+        let font = ::test_font();
+        let max_width = width("abc", &font, FONT_SIZE) + 1.0;
+        assert_eq!(wrapped_lines("abcdefgh", &font, max_width), vec!["abc", "def", "gh"]);
+        assert_eq!(wrapped_lines("a bcdefgh", &font, max_width), vec!["a", "bcd", "efg", "h"]);
+        // combining marks stay with the char before them
+        let max_width = width("ee", &font, FONT_SIZE) + 1.0;
+        assert_eq!(wrapped_lines("e\u{301}e\u{301}e\u{301}", &font, max_width),
+                   vec!["e\u{301}e\u{301}", "e\u{301}"]);
+        // a single grapheme wider than the line still takes a line of its own
+        assert_eq!(wrapped_lines("ab", &font, 1.0), vec!["a", "b"]);
+    }
+
+    /// The positioned glyphs of `text` justified in `max_width`, along with the line infos
+    fn justified(text: &str, font: &Font, max_width: f32) -> (Vec<::PositionedGlyph>, Vec<LineInfo>) {
+        let rect = Rect::new(Point::new(0.0, 0.0), Size::new(max_width, 1000.0));
+        let glyphs = ::get_positioned_glyphs(text, rect, font, FONT_SIZE, FONT_SIZE, Wrap::Unicode, Align::Justify);
+        let infos = LineInfos::new(text, font, FONT_SIZE, Wrap::Unicode, max_width).collect();
+        (glyphs, infos)
+    }
+
+    fn glyph_end(glyph: &::PositionedGlyph) -> f32 {
+        glyph.position().x + glyph.unpositioned().h_metrics().advance_width
+    }
+
+    #[test]
+    fn justified_line_fills_width() {
+        let font = ::test_font();
+        let max_width = width("aa bb cc", &font, FONT_SIZE) + 20.0;
+        let (glyphs, infos) = justified("aa bb cc dd", &font, max_width);
+        assert_eq!(infos.len(), 2);
+        let spacing = justified_word_spacing("aa bb cc", &infos[0], max_width);
+        assert!((spacing - (max_width - infos[0].width) / 2.0).abs() < 0.01);
+        // the last glyph of the wrapped line ends at the right edge
+        assert!((glyph_end(&glyphs[7]) - max_width).abs() < 0.01);
+        let line_x = align_line(&infos[0], Align::Justify, Range::new(0.0, max_width));
+        assert_eq!((line_x.start, line_x.end), (0.0, max_width));
+    }
+
+    #[test]
+    fn justified_last_line_start_aligned() {
+        let font = ::test_font();
+        let max_width = width("aa bb cc", &font, FONT_SIZE) + 20.0;
+        let text = "aa bb cc dd d";
+        let (glyphs, infos) = justified(text, &font, max_width);
+        assert_eq!(&text[infos[1].byte_range()], "dd d");
+        assert!(infos[1].ends_paragraph());
+        assert_eq!(justified_word_spacing(&text[infos[1].byte_range()], &infos[1], max_width), 0.0);
+        let line_x = align_line(&infos[1], Align::Justify, Range::new(0.0, max_width));
+        assert_eq!((line_x.start, line_x.end), (0.0, infos[1].width));
+        // the words of the last line keep their natural spacing
+        let first = infos[0].char_range().len();
+        assert_eq!(glyphs[first].position().x, 0.0);
+        assert!((glyph_end(glyphs.last().unwrap()) - infos[1].width).abs() < 0.01);
+        // as do the lines before a newline
+        let (_, infos) = justified("aa\nbb cc dd", &font, max_width);
+        assert!(infos[0].ends_paragraph());
+    }
+
+    #[test]
+    fn justified_single_word_not_stretched() {
+        let font = ::test_font();
+        let max_width = width("aaaaaa", &font, FONT_SIZE) + 20.0;
+        let (glyphs, infos) = justified("aaaaaa bb", &font, max_width);
+        assert!(!infos[0].ends_paragraph());
+        assert_eq!(justified_word_spacing("aaaaaa", &infos[0], max_width), 0.0);
+        assert!((glyph_end(&glyphs[5]) - infos[0].width).abs() < 0.01);
+        // spaces at the ends of a line aren't stretched either
+        assert_eq!(justified_word_spacing(" aaaaaa ", &infos[0], max_width), 0.0);
     }
-    total_w as f32
 }
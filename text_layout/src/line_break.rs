@@ -0,0 +1,378 @@
+//! Line break opportunities from the Unicode line breaking algorithm (UAX #14).
+//!
+//! Character classes cover the common scripts, punctuation, CJK and emoji rather than the full
+//! Unicode tables. Scripts written without spaces between words, such as Thai, would need a
+//! dictionary and are treated as alphabetic.
+
+use segment;
+
+/// Soft hyphen, an invisible break opportunity shown as a hyphen when a line breaks there
+pub const SOFT_HYPHEN: char = '\u{AD}';
+
+/// Line breaking class, after resolving the classes that UAX #14 leaves to the implementation
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum LineBreakClass {
+    /// Mandatory break
+    BK,
+    CR,
+    LF,
+    /// Next line
+    NL,
+    /// Space
+    SP,
+    /// Zero width space
+    ZW,
+    /// Zero width joiner
+    Zwj,
+    /// Word joiner
+    WJ,
+    /// Non-breaking ("glue")
+    GL,
+    /// Combining mark
+    CM,
+    /// Break after
+    BA,
+    /// Hyphen
+    HY,
+    /// Break before
+    BB,
+    /// Break opportunity before and after
+    B2,
+    /// Close punctuation
+    CL,
+    /// Close parenthesis
+    CP,
+    /// Open punctuation
+    OP,
+    /// Quotation
+    QU,
+    /// Exclamation or interrogation
+    EX,
+    /// Infix numeric separator
+    IS,
+    /// Symbols allowing break after
+    SY,
+    /// Nonstarter
+    NS,
+    /// Prefix numeric
+    PR,
+    /// Postfix numeric
+    PO,
+    /// Numeric
+    NU,
+    /// Inseparable
+    IN,
+    /// Hebrew letter
+    HL,
+    /// Alphabetic
+    AL,
+    /// Ideographic
+    ID,
+    /// Emoji modifier
+    EM,
+    /// Regional indicator
+    RI,
+    /// Hangul syllables and jamo
+    H2,
+    H3,
+    JL,
+    JV,
+    JT,
+}
+
+use self::LineBreakClass::*;
+
+fn line_break_class(ch: char) -> LineBreakClass {
+    let cp = ch as u32;
+    match cp {
+        0x000B | 0x000C | 0x2028 | 0x2029 => BK,
+        0x000D => CR,
+        0x000A => LF,
+        0x0085 => NL,
+        0x0020 => SP,
+        0x200B => ZW,
+        0x200D => Zwj,
+        0x2060 | 0xFEFF => WJ,
+        0x00A0 | 0x034F | 0x180E | 0x2007 | 0x2011 | 0x202F | 0x0F08 | 0x0F0C | 0x0F12 => GL,
+        0x0009 | 0x00AD | 0x007C | 0x058A | 0x0F0B | 0x1680 | 0x2000..=0x2006 | 0x2008..=0x200A |
+        0x2010 | 0x2012 | 0x2013 | 0x2027 | 0x205F | 0x3000 => BA,
+        0x002D => HY,
+        0x00B4 | 0x02C8 | 0x02CC | 0x02DF | 0x0F01..=0x0F04 => BB,
+        0x2014 => B2,
+        0x007D | 0x0F3B | 0x0F3D | 0x2046 | 0x207E | 0x208E | 0x2309 | 0x230B | 0x232A |
+        0x3001 | 0x3002 | 0x3009 | 0x300B | 0x300D | 0x300F | 0x3011 | 0x3015 | 0x3017 |
+        0x3019 | 0x301B | 0x301E | 0x301F | 0xFE11 | 0xFE12 | 0xFE50 | 0xFE52 | 0xFF0C |
+        0xFF0E | 0xFF5D | 0xFF61 | 0xFF63 => CL,
+        0x0029 | 0x005D | 0xFF09 | 0xFF3D => CP,
+        0x0028 | 0x005B | 0x007B | 0x00A1 | 0x00BF | 0x2045 | 0x207D | 0x208D | 0x2308 |
+        0x230A | 0x2329 | 0x3008 | 0x300A | 0x300C | 0x300E | 0x3010 | 0x3014 | 0x3016 |
+        0x3018 | 0x301A | 0x301D | 0xFF08 | 0xFF3B | 0xFF5B | 0xFF62 => OP,
+        0x0022 | 0x0027 | 0x00AB | 0x00BB | 0x2018 | 0x2019 | 0x201B..=0x201F | 0x2039 |
+        0x203A => QU,
+        0x0021 | 0x003F | 0x05C6 | 0x061B | 0x061E | 0x061F | 0x06D4 | 0xFF01 | 0xFF1F => EX,
+        0x002C | 0x002E | 0x003A | 0x003B | 0x037E | 0x0589 | 0x060C | 0x060D | 0x07F8 |
+        0x2044 | 0xFE10 | 0xFE13 | 0xFE14 => IS,
+        0x002F => SY,
+        // nonstarters, including small kana
+        0x17D6 | 0x203C | 0x203D | 0x2047..=0x2049 | 0x3005 | 0x301C | 0x303B | 0x303C |
+        0x309B..=0x309E | 0x30A0 | 0x30FB | 0x30FD | 0x30FE | 0xA015 | 0xFE54 | 0xFE55 |
+        0xFF1A | 0xFF1B | 0xFF65 | 0xFF9E | 0xFF9F | 0x3041 | 0x3043 | 0x3045 | 0x3047 |
+        0x3049 | 0x3063 | 0x3083 | 0x3085 | 0x3087 | 0x308E | 0x3095 | 0x3096 | 0x30A1 |
+        0x30A3 | 0x30A5 | 0x30A7 | 0x30A9 | 0x30C3 | 0x30E3 | 0x30E5 | 0x30E7 | 0x30EE |
+        0x30F5 | 0x30F6 | 0x30FC | 0x31F0..=0x31FF | 0xFF67..=0xFF70 => NS,
+        0x0024 | 0x002B | 0x005C | 0x00A3..=0x00A5 | 0x00B1 | 0x2116 | 0x2212 |
+        0x20A0..=0x20CF | 0xFFE1 | 0xFFE5 | 0xFFE6 => PR,
+        0x0025 | 0x00A2 | 0x00B0 | 0x0609..=0x060B | 0x066A | 0x2030..=0x2037 | 0x2103 |
+        0x2109 | 0xFF05 | 0xFFE0 => PO,
+        0x0030..=0x0039 | 0x0660..=0x0669 | 0x066B | 0x066C | 0x06F0..=0x06F9 |
+        0x0966..=0x096F | 0x0E50..=0x0E59 => NU,
+        0x2024..=0x2026 | 0x22EF | 0xFE19 => IN,
+        0x05D0..=0x05EA | 0x05EF..=0x05F2 | 0xFB1D | 0xFB1F..=0xFB28 | 0xFB2A..=0xFB4F => HL,
+        0x1F1E6..=0x1F1FF => RI,
+        0x1F3FB..=0x1F3FF => EM,
+        0x1100..=0x115F | 0xA960..=0xA97C => JL,
+        0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => JV,
+        0x11A8..=0x11FF | 0xD7CB..=0xD7FB => JT,
+        0xAC00..=0xD7A3 => match (cp - 0xAC00) % 28 {
+            0 => H2,
+            _ => H3,
+        },
+        0x2E80..=0x2FFF | 0x3003 | 0x3004 | 0x3006 | 0x3007 | 0x3012 | 0x3013 |
+        0x3020..=0x3029 | 0x3030..=0x303A | 0x303D..=0x303F | 0x3040..=0x30FF |
+        0x3105..=0x31EF | 0x3200..=0x4DBF | 0x4E00..=0x9FFF | 0xA000..=0xA48F |
+        0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF02..=0xFF60 | 0xFFE2..=0xFFE4 |
+        0x20000..=0x3FFFD => ID,
+        0x0000..=0x0008 | 0x000E..=0x001F | 0x007F..=0x0084 | 0x0086..=0x009F => CM,
+        _ if segment::is_extend(cp) || segment::is_spacing_mark(cp) => CM,
+        _ if segment::is_extended_pictographic(cp) => ID,
+        _ => AL,
+    }
+}
+
+fn is_hard_break(class: LineBreakClass) -> bool {
+    class == BK || class == CR || class == LF || class == NL
+}
+
+fn is_letter(class: LineBreakClass) -> bool {
+    class == AL || class == HL
+}
+
+fn is_hangul(class: LineBreakClass) -> bool {
+    class == JL || class == JV || class == JT || class == H2 || class == H3
+}
+
+/// The classes around a possible break, for `is_break`
+struct Context {
+    before: LineBreakClass,
+    after: LineBreakClass,
+    /// The class before `before`
+    before_before: Option<LineBreakClass>,
+    /// The last class before the break that is not a space
+    before_spaces: Option<LineBreakClass>,
+    /// Whether the char before the break is a zero width joiner, rather than a combining mark
+    after_zwj: bool,
+    /// Number of regional indicators in a row before the break
+    regional_indicators: usize,
+}
+
+/// Whether a line can break between two chars, from the rules of UAX #14 after LB9 and LB10
+fn is_break(context: &Context) -> bool {
+    let Context { before, after, before_before, before_spaces, after_zwj, regional_indicators } = *context;
+    match (before, after) {
+        // LB4, LB5, LB6
+        (CR, LF) => false,
+        (a, _) if is_hard_break(a) => true,
+        (_, b) if is_hard_break(b) => false,
+        // LB7, LB8, LB8a
+        (_, SP) | (_, ZW) => false,
+        _ if before_spaces == Some(ZW) => true,
+        _ if after_zwj => false,
+        // LB11, LB12, LB12a
+        (_, WJ) | (WJ, _) | (GL, _) => false,
+        (a, GL) if a != SP && a != BA && a != HY => false,
+        // LB13
+        (_, CL) | (_, CP) | (_, EX) | (_, IS) | (_, SY) => false,
+        // LB14, LB15, LB16, LB17
+        _ if before_spaces == Some(OP) => false,
+        (_, OP) if before_spaces == Some(QU) => false,
+        (_, NS) if before_spaces == Some(CL) || before_spaces == Some(CP) => false,
+        (_, B2) if before_spaces == Some(B2) => false,
+        // LB18
+        (SP, _) => true,
+        // LB19, LB21, LB21a, LB21b, LB22
+        (_, QU) | (QU, _) => false,
+        (_, BA) | (_, HY) | (_, NS) | (BB, _) => false,
+        (HY, _) | (BA, _) if before_before == Some(HL) => false,
+        (SY, HL) | (_, IN) => false,
+        // LB23, LB23a, LB24
+        (a, NU) if is_letter(a) => false,
+        (NU, b) if is_letter(b) => false,
+        (PR, ID) | (PR, EM) | (ID, PO) | (EM, PO) => false,
+        (PR, b) | (PO, b) if is_letter(b) => false,
+        (a, PR) | (a, PO) if is_letter(a) => false,
+        // LB25
+        (CL, PO) | (CP, PO) | (CL, PR) | (CP, PR) | (NU, PO) | (NU, PR) | (PO, OP) |
+        (PO, NU) | (PR, OP) | (PR, NU) | (HY, NU) | (IS, NU) | (NU, NU) | (SY, NU) => false,
+        // LB26, LB27
+        (JL, JL) | (JL, JV) | (JL, H2) | (JL, H3) | (JV, JV) | (JV, JT) | (H2, JV) |
+        (H2, JT) | (JT, JT) | (H3, JT) => false,
+        (a, PO) if is_hangul(a) => false,
+        (PR, b) if is_hangul(b) => false,
+        // LB28, LB29, LB30
+        (a, b) if is_letter(a) && is_letter(b) => false,
+        (IS, b) if is_letter(b) => false,
+        (a, OP) if is_letter(a) || a == NU => false,
+        (CP, b) if is_letter(b) || b == NU => false,
+        // LB30a: flags are pairs of regional indicators
+        (RI, RI) => regional_indicators & 1 == 0,
+        // LB30b
+        (ID, EM) => false,
+        // LB31
+        _ => true,
+    }
+}
+
+/// The byte indices in `text` where a line can start, other than the start of the text.
+///
+/// Includes the position after each newline, and the position after any spaces following a
+/// break opportunity, so that spaces stay at the end of the line before a break.
+pub fn break_opportunities(text: &str) -> Vec<usize> {
+    let mut opportunities = Vec::new();
+    let mut classes: Vec<LineBreakClass> = Vec::new();
+    let mut last_raw = None;
+    let mut regional_indicators = 0;
+    for (byte_i, ch) in text.char_indices() {
+        let raw = line_break_class(ch);
+        let attaches = (raw == CM || raw == Zwj) && classes.last().map_or(false, |&class| {
+            !is_hard_break(class) && class != SP && class != ZW
+        });
+        // LB9: combining marks take the class of the char they are attached to,
+        // LB10: otherwise they are alphabetic
+        let class = match raw {
+            _ if attaches => *classes.last().unwrap(),
+            CM | Zwj => AL,
+            _ => raw,
+        };
+        if let Some(&before) = classes.last() {
+            let context = Context {
+                before: before,
+                after: class,
+                before_before: classes.len().checked_sub(2).map(|i| classes[i]),
+                before_spaces: classes.iter().rev().cloned().find(|&class| class != SP),
+                after_zwj: last_raw == Some(Zwj),
+                regional_indicators: regional_indicators,
+            };
+            if !attaches && is_break(&context) {
+                opportunities.push(byte_i);
+            }
+        }
+        if !attaches {
+            regional_indicators = if class == RI { regional_indicators + 1 } else { 0 };
+        }
+        classes.push(class);
+        last_raw = Some(raw);
+    }
+    opportunities
+}
+
+/// Inserts soft hyphens into each word of `text`, at the byte offsets within the word returned
+/// by `hyphenate`, so that `Wrap::Unicode` can break long words with a hyphen.
+///
+/// Words are found per UAX #29, see `segment::word_boundaries`.
+pub fn insert_soft_hyphens<F>(text: &str, hyphenate: F) -> String
+    where F: Fn(&str) -> Vec<usize>
+{
+    let mut hyphenated = String::with_capacity(text.len());
+    for bounds in segment::word_boundaries(text).windows(2) {
+        let word = &text[bounds[0]..bounds[1]];
+        if !segment::is_word(word) {
+            hyphenated.push_str(word);
+            continue;
+        }
+        let mut last = 0;
+        let mut points = hyphenate(word);
+        points.sort();
+        for point in points {
+            if point > last && point < word.len() && word.is_char_boundary(point) {
+                hyphenated.push_str(&word[last..point]);
+                hyphenated.push(SOFT_HYPHEN);
+                last = point;
+            }
+        }
+        hyphenated.push_str(&word[last..]);
+    }
+    hyphenated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text split at each break opportunity
+    fn segments(text: &str) -> Vec<&str> {
+        let mut bounds = vec![0];
+        bounds.extend(break_opportunities(text));
+        bounds.push(text.len());
+        bounds.windows(2).map(|range| &text[range[0]..range[1]]).collect()
+    }
+
+    #[test]
+    fn spaces_stay_before_break() {
+        assert_eq!(segments("a  b c"), vec!["a  ", "b ", "c"]);
+        assert_eq!(segments("a\nb"), vec!["a\n", "b"]);
+    }
+
+    #[test]
+    fn no_break_before_closing_punctuation() {
+        assert_eq!(segments("Hello, world."), vec!["Hello, ", "world."]);
+        assert_eq!(segments("a (b) c"), vec!["a ", "(b) ", "c"]);
+        assert_eq!(segments("Wow !"), vec!["Wow !"]);
+        assert_eq!(segments("[x]}"), vec!["[x]}"]);
+    }
+
+    #[test]
+    fn no_break_at_no_break_space() {
+        assert_eq!(segments("10\u{A0}kg of"), vec!["10\u{A0}kg ", "of"]);
+        assert_eq!(segments("a\u{2060}b"), vec!["a\u{2060}b"]);
+    }
+
+    #[test]
+    fn cjk_ideographs() {
+        assert_eq!(segments("漢字かな"), vec!["漢", "字", "か", "な"]);
+        // no break before the ideographic full stop or a small kana
+        assert_eq!(segments("日本。"), vec!["日", "本。"]);
+        assert_eq!(segments("ちょっと"), vec!["ちょっ", "と"]);
+    }
+
+    #[test]
+    fn url_slashes() {
+        assert_eq!(segments("http://example.com/path/to"),
+                   vec!["http://", "example.com/", "path/", "to"]);
+        // no break between a slash and a number
+        assert_eq!(segments("1/2"), vec!["1/2"]);
+    }
+
+    #[test]
+    fn hyphens_and_soft_hyphens() {
+        assert_eq!(segments("well-known"), vec!["well-", "known"]);
+        assert_eq!(segments("hy\u{AD}phen"), vec!["hy\u{AD}", "phen"]);
+        assert_eq!(segments("-1"), vec!["-1"]);
+    }
+
+    #[test]
+    fn combining_marks_and_emoji() {
+        assert_eq!(segments("e\u{301}e"), vec!["e\u{301}e"]);
+        assert_eq!(segments("🇫🇷🇩🇪"), vec!["🇫🇷", "🇩🇪"]);
+        assert_eq!(segments("👍\u{1F3FD}"), vec!["👍\u{1F3FD}"]);
+    }
+
+    #[test]
+    fn soft_hyphens_inserted_into_words() {
+        let hyphenate = |word: &str| if word == "hyphenation" { vec![6, 2, 20] } else { vec![] };
+        assert_eq!(insert_soft_hyphens("a hyphenation, hyphenation", hyphenate),
+                   "a hy\u{AD}phen\u{AD}ation, hy\u{AD}phen\u{AD}ation");
+        // points at the ends of the word or within a char are ignored
+        let hyphenate = |_: &str| vec![0, 1, 2, 4];
+        assert_eq!(insert_soft_hyphens("é ab", hyphenate), "é a\u{AD}b");
+    }
+}
@@ -0,0 +1,390 @@
+//! Segmentation of text into extended grapheme clusters and words (UAX #29).
+//!
+//! Character properties cover the common scripts, combining marks and emoji, rather than the
+//! full Unicode tables.
+
+/// Grapheme cluster break property
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum GraphemeClass {
+    CR,
+    LF,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    SpacingMark,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    ExtendedPictographic,
+    Other,
+}
+
+pub(super) fn is_extend(cp: u32) -> bool {
+    match cp {
+        0x0300..=0x036F | 0x0483..=0x0489 | 0x0591..=0x05BD | 0x05BF | 0x05C1 | 0x05C2 |
+        0x05C4 | 0x05C5 | 0x05C7 | 0x0610..=0x061A | 0x064B..=0x065F | 0x0670 |
+        0x06D6..=0x06DC | 0x06DF..=0x06E4 | 0x06E7 | 0x06E8 | 0x06EA..=0x06ED |
+        0x0900..=0x0902 | 0x093A | 0x093C | 0x0941..=0x0948 | 0x094D | 0x0951..=0x0957 |
+        0x0962 | 0x0963 | 0x0E31 | 0x0E34..=0x0E3A | 0x0E47..=0x0E4E | 0x1AB0..=0x1AFF |
+        0x1DC0..=0x1DFF | 0x200C | 0x20D0..=0x20FF | 0x302A..=0x302F | 0x3099 | 0x309A |
+        0xFE00..=0xFE0F | 0xFE20..=0xFE2F | 0x1F3FB..=0x1F3FF | 0xE0020..=0xE007F |
+        0xE0100..=0xE01EF => true,
+        _ => false,
+    }
+}
+
+pub(super) fn is_spacing_mark(cp: u32) -> bool {
+    match cp {
+        0x0903 | 0x093B | 0x093E..=0x0940 | 0x0949..=0x094C | 0x094E | 0x094F | 0x0E33 => true,
+        _ => false,
+    }
+}
+
+pub(super) fn is_extended_pictographic(cp: u32) -> bool {
+    match cp {
+        0x00A9 | 0x00AE | 0x203C | 0x2049 | 0x2122 | 0x2139 | 0x2194..=0x2199 | 0x21A9 |
+        0x21AA | 0x231A | 0x231B | 0x2328 | 0x23CF | 0x23E9..=0x23F3 | 0x23F8..=0x23FA |
+        0x24C2 | 0x25AA | 0x25AB | 0x25B6 | 0x25C0 | 0x25FB..=0x25FE | 0x2600..=0x27BF |
+        0x2934 | 0x2935 | 0x2B05..=0x2B07 | 0x2B1B | 0x2B1C | 0x2B50 | 0x2B55 | 0x3030 |
+        0x303D | 0x3297 | 0x3299 | 0x1F000..=0x1F0FF | 0x1F10D..=0x1F10F | 0x1F12F |
+        0x1F16C..=0x1F171 | 0x1F17E | 0x1F17F | 0x1F18E | 0x1F191..=0x1F19A |
+        0x1F1AD..=0x1F1E5 | 0x1F201..=0x1FAFF | 0x1FC00..=0x1FFFD => true,
+        _ => false,
+    }
+}
+
+fn grapheme_class(ch: char) -> GraphemeClass {
+    use self::GraphemeClass::*;
+    let cp = ch as u32;
+    match cp {
+        0x000D => CR,
+        0x000A => LF,
+        0x200D => Zwj,
+        0x1F1E6..=0x1F1FF => RegionalIndicator,
+        0x0000..=0x001F | 0x007F..=0x009F | 0x00AD | 0x200B | 0x200E | 0x200F |
+        0x2028..=0x202E | 0x2060..=0x206F | 0xFEFF | 0xFFF0..=0xFFFB => Control,
+        0x1100..=0x115F | 0xA960..=0xA97C => L,
+        0x1160..=0x11A7 | 0xD7B0..=0xD7C6 => V,
+        0x11A8..=0x11FF | 0xD7CB..=0xD7FB => T,
+        0xAC00..=0xD7A3 => match (cp - 0xAC00) % 28 {
+            0 => Lv,
+            _ => Lvt,
+        },
+        _ if is_extend(cp) => Extend,
+        _ if is_spacing_mark(cp) => SpacingMark,
+        _ if is_extended_pictographic(cp) => ExtendedPictographic,
+        _ => Other,
+    }
+}
+
+/// Whether there is a grapheme cluster boundary between chars of class `before` and `after`.
+/// `emoji_zwj` is whether `before` is a zero width joiner ending an emoji sequence, and
+/// `odd_regional_indicators` whether there is an odd number of regional indicators in a row up to
+/// and including `before`.
+fn is_grapheme_break(before: GraphemeClass,
+                     after: GraphemeClass,
+                     emoji_zwj: bool,
+                     odd_regional_indicators: bool)
+                     -> bool {
+    use self::GraphemeClass::*;
+    match (before, after) {
+        // GB3, GB4, GB5
+        (CR, LF) => false,
+        (CR, _) | (LF, _) | (Control, _) | (_, CR) | (_, LF) | (_, Control) => true,
+        // GB6, GB7, GB8: Hangul syllables
+        (L, L) | (L, V) | (L, Lv) | (L, Lvt) | (Lv, V) | (Lv, T) | (V, V) | (V, T) |
+        (Lvt, T) | (T, T) => false,
+        // GB9, GB9a
+        (_, Extend) | (_, Zwj) | (_, SpacingMark) => false,
+        // GB11: emoji joined into a sequence
+        (Zwj, ExtendedPictographic) => !emoji_zwj,
+        // GB12, GB13: flags are pairs of regional indicators
+        (RegionalIndicator, RegionalIndicator) => !odd_regional_indicators,
+        _ => true,
+    }
+}
+
+/// The byte indices of the grapheme cluster boundaries in `text`, including the start and end
+/// of the text.
+pub fn grapheme_boundaries(text: &str) -> Vec<usize> {
+    let mut boundaries = vec![0];
+    let mut last = None;
+    // whether the chars so far end with an emoji followed by any extending chars
+    let mut emoji = false;
+    let mut emoji_zwj = false;
+    let mut odd_regional_indicators = false;
+    for (byte_i, ch) in text.char_indices() {
+        let class = grapheme_class(ch);
+        if let Some(last) = last {
+            if is_grapheme_break(last, class, emoji_zwj, odd_regional_indicators) {
+                boundaries.push(byte_i);
+            }
+        }
+        emoji_zwj = emoji && class == GraphemeClass::Zwj;
+        emoji = match class {
+            GraphemeClass::ExtendedPictographic => true,
+            GraphemeClass::Extend => emoji,
+            _ => false,
+        };
+        odd_regional_indicators = class == GraphemeClass::RegionalIndicator && !odd_regional_indicators;
+        last = Some(class);
+    }
+    if !text.is_empty() {
+        boundaries.push(text.len());
+    }
+    boundaries
+}
+
+/// The grapheme cluster boundary after the byte index `byte`, or the end of the text
+pub fn next_grapheme_boundary(text: &str, byte: usize) -> usize {
+    grapheme_boundaries(text).into_iter().find(|&boundary| boundary > byte).unwrap_or(text.len())
+}
+
+/// The grapheme cluster boundary before the byte index `byte`, or the start of the text
+pub fn previous_grapheme_boundary(text: &str, byte: usize) -> usize {
+    grapheme_boundaries(text).into_iter().rev().find(|&boundary| boundary < byte).unwrap_or(0)
+}
+
+/// Word break property
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum WordClass {
+    CR,
+    LF,
+    Newline,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Format,
+    Katakana,
+    HebrewLetter,
+    ALetter,
+    SingleQuote,
+    DoubleQuote,
+    MidNumLet,
+    MidLetter,
+    MidNum,
+    Numeric,
+    ExtendNumLet,
+    WSegSpace,
+    ExtendedPictographic,
+    Other,
+}
+
+fn word_class(ch: char) -> WordClass {
+    use self::WordClass::*;
+    let cp = ch as u32;
+    match cp {
+        0x000D => CR,
+        0x000A => LF,
+        0x000B | 0x000C | 0x0085 | 0x2028 | 0x2029 => Newline,
+        0x200D => Zwj,
+        0x1F1E6..=0x1F1FF => RegionalIndicator,
+        0x00AD | 0x0600..=0x0605 | 0x061C | 0x06DD | 0x200E | 0x200F | 0x202A..=0x202E |
+        0x2060..=0x2064 | 0xFEFF => Format,
+        0x3031..=0x3035 | 0x309B | 0x309C | 0x30A0..=0x30FA | 0x30FC..=0x30FF |
+        0x31F0..=0x31FF | 0xFF66..=0xFF9D => Katakana,
+        0x05D0..=0x05EA | 0x05EF..=0x05F2 | 0xFB1D | 0xFB1F..=0xFB28 | 0xFB2A..=0xFB4F => HebrewLetter,
+        0x0027 => SingleQuote,
+        0x0022 => DoubleQuote,
+        0x002E | 0x2018 | 0x2019 | 0x2024 | 0xFE52 | 0xFF07 | 0xFF0E => MidNumLet,
+        0x003A | 0x00B7 | 0x0387 | 0x055F | 0x05F4 | 0x2027 | 0xFE13 | 0xFE55 | 0xFF1A => MidLetter,
+        0x002C | 0x003B | 0x037E | 0x0589 | 0x060C | 0x060D | 0x066C | 0x07F8 | 0x2044 |
+        0xFE10 | 0xFE14 | 0xFE50 | 0xFE54 | 0xFF0C | 0xFF1B => MidNum,
+        0x0030..=0x0039 | 0x0660..=0x0669 | 0x066B | 0x06F0..=0x06F9 | 0x0966..=0x096F |
+        0xFF10..=0xFF19 => Numeric,
+        0x005F | 0x202F | 0x203F | 0x2040 | 0x2054 | 0xFE33 | 0xFE34 | 0xFE4D..=0xFE4F |
+        0xFF3F => ExtendNumLet,
+        0x0020 | 0x1680 | 0x2000..=0x2006 | 0x2008..=0x200A | 0x205F | 0x3000 => WSegSpace,
+        _ if is_extend(cp) || is_spacing_mark(cp) => Extend,
+        _ if is_extended_pictographic(cp) => ExtendedPictographic,
+        // ideographs, kana and scripts without spaces between words are not letters here,
+        // so each of their chars is a word of its own
+        0x0E00..=0x0EFF | 0x1000..=0x109F | 0x1780..=0x17FF | 0x3040..=0x309F |
+        0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x3FFFF => Other,
+        _ if ch.is_alphabetic() => ALetter,
+        _ => Other,
+    }
+}
+
+/// A char along with any extending chars after it, which are treated as one (WB4)
+struct WordUnit {
+    start: usize,
+    class: WordClass,
+    /// Whether the unit ends with a zero width joiner
+    zwj: bool,
+}
+
+fn is_ahletter(class: WordClass) -> bool {
+    class == WordClass::ALetter || class == WordClass::HebrewLetter
+}
+
+fn is_mid_num_let_q(class: WordClass) -> bool {
+    class == WordClass::MidNumLet || class == WordClass::SingleQuote
+}
+
+/// Whether there is a word boundary before the unit at `i`
+fn is_word_break(units: &[WordUnit], i: usize) -> bool {
+    use self::WordClass::*;
+    let class_at = |i: Option<usize>| i.and_then(|i| units.get(i)).map(|unit| unit.class);
+    let (before, after) = (units[i - 1].class, units[i].class);
+    let before_before = class_at(i.checked_sub(2));
+    let after_after = class_at(Some(i + 1));
+    let ahletter = |class: Option<WordClass>| class.map_or(false, is_ahletter);
+    let numeric = |class: Option<WordClass>| class == Some(Numeric);
+    match (before, after) {
+        // WB3, WB3a, WB3b
+        (CR, LF) => false,
+        (CR, _) | (LF, _) | (Newline, _) | (_, CR) | (_, LF) | (_, Newline) => true,
+        // WB3c, WB3d
+        (_, ExtendedPictographic) if units[i - 1].zwj => false,
+        (WSegSpace, WSegSpace) => false,
+        // WB5
+        (a, b) if is_ahletter(a) && is_ahletter(b) => false,
+        // WB6, WB7
+        (a, b) if is_ahletter(a) && (b == MidLetter || is_mid_num_let_q(b)) && ahletter(after_after) => false,
+        (a, b) if (a == MidLetter || is_mid_num_let_q(a)) && is_ahletter(b) && ahletter(before_before) => false,
+        // WB7a, WB7b, WB7c
+        (HebrewLetter, SingleQuote) => false,
+        (HebrewLetter, DoubleQuote) if after_after == Some(HebrewLetter) => false,
+        (DoubleQuote, HebrewLetter) if before_before == Some(HebrewLetter) => false,
+        // WB8, WB9, WB10
+        (Numeric, Numeric) => false,
+        (a, Numeric) if is_ahletter(a) => false,
+        (Numeric, b) if is_ahletter(b) => false,
+        // WB11, WB12
+        (a, Numeric) if (a == MidNum || is_mid_num_let_q(a)) && numeric(before_before) => false,
+        (Numeric, b) if (b == MidNum || is_mid_num_let_q(b)) && numeric(after_after) => false,
+        // WB13, WB13a, WB13b
+        (Katakana, Katakana) => false,
+        (a, ExtendNumLet) if is_ahletter(a) || a == Numeric || a == Katakana || a == ExtendNumLet => false,
+        (ExtendNumLet, b) if is_ahletter(b) || b == Numeric || b == Katakana => false,
+        // WB15, WB16: flags are pairs of regional indicators
+        (RegionalIndicator, RegionalIndicator) => {
+            let in_a_row = units[..i].iter().rev()
+                .take_while(|unit| unit.class == RegionalIndicator)
+                .count();
+            in_a_row & 1 == 0
+        }
+        _ => true,
+    }
+}
+
+/// The byte indices of the word boundaries in `text`, including the start and end of the text.
+///
+/// Words, runs of whitespace and each punctuation char are separate segments, see `is_word`.
+pub fn word_boundaries(text: &str) -> Vec<usize> {
+    let mut units: Vec<WordUnit> = Vec::new();
+    for (byte_i, ch) in text.char_indices() {
+        let class = word_class(ch);
+        let extends = class == WordClass::Extend || class == WordClass::Format || class == WordClass::Zwj;
+        match units.last_mut() {
+            Some(ref mut unit) if extends && unit.class != WordClass::CR &&
+                unit.class != WordClass::LF && unit.class != WordClass::Newline => {
+                unit.zwj = class == WordClass::Zwj;
+                continue;
+            }
+            _ => (),
+        }
+        units.push(WordUnit {
+            start: byte_i,
+            class: class,
+            zwj: class == WordClass::Zwj,
+        });
+    }
+    let mut boundaries = vec![0];
+    for i in 1..units.len() {
+        if is_word_break(&units, i) {
+            boundaries.push(units[i].start);
+        }
+    }
+    if !text.is_empty() {
+        boundaries.push(text.len());
+    }
+    boundaries
+}
+
+/// Whether a segment between word boundaries is a word, rather than whitespace or punctuation
+pub fn is_word(segment: &str) -> bool {
+    segment.chars().any(|ch| ch.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        word_boundaries(text).windows(2).map(|range| &text[range[0]..range[1]]).collect()
+    }
+
+    #[test]
+    fn combining_marks() {
+        assert_eq!(grapheme_boundaries("e\u{301}a"), vec![0, 3, 4]);
+        assert_eq!(grapheme_boundaries("a\u{301}\u{323}"), vec![0, 5]);
+        assert_eq!(next_grapheme_boundary("e\u{301}a", 0), 3);
+        assert_eq!(previous_grapheme_boundary("e\u{301}a", 3), 0);
+    }
+
+    #[test]
+    fn emoji_sequences() {
+        // man, zwj, woman, zwj, girl
+        assert_eq!(grapheme_boundaries("👨\u{200D}👩\u{200D}👧"), vec![0, 18]);
+        assert_eq!(grapheme_boundaries("👍\u{1F3FD}👍"), vec![0, 8, 12]);
+        // a zwj not following an emoji doesn't join
+        assert_eq!(grapheme_boundaries("a\u{200D}👧"), vec![0, 4, 8]);
+    }
+
+    #[test]
+    fn regional_indicator_flags() {
+        assert_eq!(grapheme_boundaries("🇫🇷🇩🇪"), vec![0, 8, 16]);
+        assert_eq!(grapheme_boundaries("🇫🇷🇩"), vec![0, 8, 12]);
+        assert_eq!(words("🇫🇷🇩🇪"), vec!["🇫🇷", "🇩🇪"]);
+    }
+
+    #[test]
+    fn hangul_syllables() {
+        // leading consonant, vowel and trailing consonant jamo
+        assert_eq!(grapheme_boundaries("\u{1100}\u{1161}\u{11A8}"), vec![0, 9]);
+        // precomposed syllable with a trailing consonant jamo
+        assert_eq!(grapheme_boundaries("\u{AC00}\u{11A8}"), vec![0, 6]);
+        assert_eq!(grapheme_boundaries("한국"), vec![0, 3, 6]);
+        assert_eq!(words("한국 말"), vec!["한국", " ", "말"]);
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!(grapheme_boundaries("a\r\nb"), vec![0, 1, 3, 4]);
+        assert_eq!(words("a\r\nb"), vec!["a", "\r\n", "b"]);
+    }
+
+    #[test]
+    fn words_with_apostrophes() {
+        assert_eq!(words("can't stop"), vec!["can't", " ", "stop"]);
+        assert_eq!(words("dogs' toys"), vec!["dogs", "'", " ", "toys"]);
+        assert_eq!(words("'quoted'"), vec!["'", "quoted", "'"]);
+    }
+
+    #[test]
+    fn words_with_digits() {
+        assert_eq!(words("3.14 a1 1,000.5"), vec!["3.14", " ", "a1", " ", "1,000.5"]);
+        assert_eq!(words("x,y 2."), vec!["x", ",", "y", " ", "2", "."]);
+    }
+
+    #[test]
+    fn words_with_punctuation() {
+        assert_eq!(words("Hello, world!"), vec!["Hello", ",", " ", "world", "!"]);
+        assert_eq!(words("e.g. snake_case"), vec!["e.g", ".", " ", "snake_case"]);
+        assert_eq!(words("a  b"), vec!["a", "  ", "b"]);
+        let segments = words("Hello, world!");
+        let is_words: Vec<bool> = segments.iter().map(|segment| is_word(segment)).collect();
+        assert_eq!(is_words, vec![true, false, false, true, false]);
+    }
+
+    #[test]
+    fn empty_text() {
+        assert_eq!(grapheme_boundaries(""), vec![0]);
+        assert_eq!(word_boundaries(""), vec![0]);
+    }
+}
@@ -0,0 +1,518 @@
+//! Layout of text made up of spans, each with its own font and size.
+
+use std::cmp;
+use std::f32;
+use std::ops;
+use rusttype::{self, Scale};
+
+use super::{Font, PositionedGlyph, Wrap, Overflow};
+use super::bidi::{self, BidiLine};
+use super::line::{self, LineInfo, LineInfos};
+use super::segment;
+use types::{Align, Rect, RectExt, Point, Size};
+
+/// A byte range of the text, laid out with its own font and size.
+///
+/// Spans should be in order and together cover the whole text.
+#[derive(Clone)]
+pub struct Span<'a> {
+    pub range: ops::Range<usize>,
+    pub font: &'a Font,
+    pub font_size: f32,
+}
+
+/// The fonts used to measure text, either one font for all of it or one per span.
+#[derive(Copy, Clone)]
+pub enum Fonts<'a> {
+    Single(&'a Font, f32),
+    /// Must contain at least one span
+    Spans(&'a [Span<'a>]),
+}
+
+impl<'a> Fonts<'a> {
+    /// The index of the span containing the character at `byte`, along with its font and size.
+    /// Characters past the last span use the last span.
+    pub fn at(&self, byte: usize) -> (usize, &'a Font, f32) {
+        match *self {
+            Fonts::Single(font, font_size) => (0, font, font_size),
+            Fonts::Spans(spans) => {
+                let index = spans.iter()
+                    .position(|span| byte < span.range.end)
+                    .unwrap_or(spans.len() - 1);
+                let span = &spans[index];
+                (index, span.font, span.font_size)
+            }
+        }
+    }
+}
+
+/// Whether `font` has a glyph for `ch`, rather than only the missing glyph
+pub fn has_glyph(font: &Font, ch: char) -> bool {
+    font.glyph(ch).map_or(false, |glyph| glyph.id().0 != 0)
+}
+
+/// Splits `text` into byte ranges that can each be drawn with one font, using for each character
+/// the first font in `fonts` that has a glyph for it, or the first font if none do.
+/// Whitespace and control characters stay with the preceding character.
+///
+/// Yields the ranges along with the index of their font, for use in `Span`s.
+pub fn split_by_font(text: &str, fonts: &[&Font]) -> Vec<(ops::Range<usize>, usize)> {
+    let mut runs: Vec<(ops::Range<usize>, usize)> = Vec::new();
+    for (byte_i, ch) in text.char_indices() {
+        let end = byte_i + ch.len_utf8();
+        let current = runs.last().map(|&(_, font)| font);
+        let font = match current {
+            Some(current) if ch.is_whitespace() || ch.is_control() => current,
+            _ => fonts.iter().position(|font| has_glyph(font, ch)).unwrap_or(0),
+        };
+        if current == Some(font) {
+            runs.last_mut().unwrap().0.end = end;
+        } else {
+            runs.push((byte_i..end, font));
+        }
+    }
+    if runs.is_empty() {
+        runs.push((0..0, 0));
+    }
+    runs
+}
+
+/// Vertical metrics of a line, fitting the tallest span in the line
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Distance from the top of the line to the baseline
+    pub ascent: f32,
+    pub height: f32,
+}
+
+/// Metrics of the line containing the byte `range`, see `LineInfo::byte_range`
+pub fn line_metrics(spans: &[Span], range: ops::Range<usize>) -> LineMetrics {
+    let (mut ascent, mut descent, mut line_gap) = (0.0, 0.0, 0.0);
+    let mut add_span = |font: &Font, font_size: f32| {
+        let v_metrics = font.v_metrics(Scale::uniform(font_size));
+        ascent = f32::max(ascent, v_metrics.ascent);
+        descent = f32::max(descent, -v_metrics.descent);
+        line_gap = f32::max(line_gap, v_metrics.line_gap);
+    };
+    let mut in_line = spans.iter()
+        .filter(|span| span.range.start < range.end && span.range.end > range.start)
+        .peekable();
+    if in_line.peek().is_none() {
+        // empty lines take the height of the span they are in
+        let (_, font, font_size) = Fonts::Spans(spans).at(range.start);
+        add_span(font, font_size);
+    }
+    for span in in_line {
+        add_span(span.font, span.font_size);
+    }
+    LineMetrics {
+        ascent: ascent,
+        height: ascent + descent + line_gap,
+    }
+}
+
+pub fn get_text_size(text: &str, spans: &[Span], wrap: Wrap) -> Size {
+    let mut size = Size::zero();
+    for line_info in LineInfos::with_spans(text, spans, wrap, f32::MAX) {
+        size.width = f32::max(size.width, line_info.width);
+        size.height += line_metrics(spans, line_info.byte_range()).height;
+    }
+    size
+}
+
+pub fn get_text_height(text: &str, spans: &[Span], wrap: Wrap, width: f32) -> f32 {
+    LineInfos::with_spans(text, spans, wrap, width)
+        .map(|line_info| line_metrics(spans, line_info.byte_range()).height)
+        .sum()
+}
+
+/// Replaces the text cut off by an overflow mode
+pub const ELLIPSIS: char = '\u{2026}';
+
+/// The text to lay out in place of `text` within `size`, with an ellipsis where text is cut off
+/// according to `overflow`. Text that fits is returned unchanged.
+///
+/// Wrapped lines that are kept end with a newline, so the result wraps the same way.
+/// Byte indices into the result don't map back to `text` where part of it is replaced.
+pub fn overflow_text(text: &str, spans: &[Span], wrap: Wrap, overflow: Overflow, size: Size) -> String {
+    let fonts = Fonts::Spans(spans);
+    let line_infos: Vec<LineInfo> = LineInfos::with_spans(text, spans, wrap, size.width).collect();
+    match overflow {
+        Overflow::Clip => text.to_owned(),
+        Overflow::EllipsisEnd => {
+            // the lines that fit within the height, or the first line if none do
+            let mut height = 0.0;
+            let fitting = line_infos.iter().take_while(|line_info| {
+                height += line_metrics(spans, line_info.byte_range()).height;
+                height <= size.height
+            }).count();
+            ellipsize_end(text, fonts, &line_infos, cmp::max(fitting, 1), size.width)
+        }
+        Overflow::MaxLines(max_lines) => {
+            ellipsize_end(text, fonts, &line_infos, cmp::max(max_lines, 1), size.width)
+        }
+        Overflow::EllipsisMiddle => {
+            let first_line = 0..text.find('\n').unwrap_or(text.len());
+            let advances = line::char_advances(text, fonts, first_line.clone());
+            let width: f32 = advances.iter().map(|&(_, advance)| advance).sum();
+            if first_line.end == text.len() && width <= size.width {
+                return text.to_owned();
+            }
+            let boundaries = segment::grapheme_boundaries(&text[first_line.clone()]);
+            let available = size.width - ellipsis_width(fonts, first_line.end / 2);
+            // as much of the start as fits in half the width, then as much of the end as fits
+            let start_end = fit_width(&advances, &boundaries, first_line.end, available / 2.0);
+            let start_width: f32 = advances.iter()
+                .take_while(|&&(byte_i, _)| byte_i < start_end)
+                .map(|&(_, advance)| advance)
+                .sum();
+            let mut end_width = 0.0;
+            let mut end_start = first_line.end;
+            for &(byte_i, advance) in advances.iter().rev() {
+                end_width += advance;
+                if end_width > available - start_width || byte_i < start_end {
+                    break;
+                }
+                if boundaries.contains(&byte_i) {
+                    end_start = byte_i;
+                }
+            }
+            format!("{}{}{}", text[..start_end].trim_end(), ELLIPSIS, text[end_start..first_line.end].trim_start())
+        }
+    }
+}
+
+/// Keeps the first `count` lines, replacing the end of any that are wider than `width` with an
+/// ellipsis, as well as the end of the last line if there are more.
+fn ellipsize_end(text: &str, fonts: Fonts, line_infos: &[LineInfo], count: usize, width: f32) -> String {
+    if line_infos.len() <= count && line_infos.iter().all(|line_info| line_info.width <= width) {
+        return text.to_owned();
+    }
+    let count = cmp::min(count, line_infos.len());
+    let mut lines = Vec::new();
+    for (i, line_info) in line_infos[..count].iter().enumerate() {
+        let line = &text[line_info.byte_range()];
+        let cut = i + 1 == count && count < line_infos.len();
+        if !cut && line_info.width <= width {
+            lines.push(line.to_owned());
+            continue;
+        }
+        let available = width - ellipsis_width(fonts, line_info.end_byte());
+        let advances = line::char_advances(text, fonts, line_info.byte_range());
+        let boundaries: Vec<usize> = segment::grapheme_boundaries(line).iter()
+            .map(|boundary| line_info.start_byte + boundary)
+            .collect();
+        let end = fit_width(&advances, &boundaries, line_info.end_byte(), available);
+        lines.push(format!("{}{}", text[line_info.start_byte..end].trim_end(), ELLIPSIS));
+    }
+    lines.join("\n")
+}
+
+/// The last of `boundaries` where the chars before it, with the given advances, fit in `width`
+fn fit_width(advances: &[(usize, f32)], boundaries: &[usize], end: usize, width: f32) -> usize {
+    let start = advances.first().map_or(end, |&(byte_i, _)| byte_i);
+    let mut fitting = start;
+    let mut total = 0.0;
+    for &(byte_i, advance) in advances {
+        if boundaries.contains(&byte_i) {
+            fitting = byte_i;
+        }
+        total += advance;
+        if total > width {
+            return fitting;
+        }
+    }
+    end
+}
+
+/// Width of the ellipsis, drawn with the font of the text at `byte`
+fn ellipsis_width(fonts: Fonts, byte: usize) -> f32 {
+    let (_, font, font_size) = fonts.at(byte);
+    font.glyph(ELLIPSIS).unwrap().scaled(Scale::uniform(font_size)).h_metrics().advance_width
+}
+
+/// The glyphs of one span within one line
+pub struct GlyphRun {
+    /// Index of the span the glyphs belong to
+    pub span: usize,
+    /// The bytes of the text the glyphs are for
+    pub byte_range: ops::Range<usize>,
+    /// Whether the glyphs are displayed right-to-left, in the reverse order of their chars
+    pub rtl: bool,
+    /// Bounds of the glyphs, with the height of the line
+    pub rect: Rect,
+    /// Position of the line's baseline on the y axis
+    pub baseline: f32,
+    pub glyphs: Vec<PositionedGlyph>,
+    /// Byte index in the text of the char each glyph is drawn for, chars without a glyph,
+    /// such as hidden soft hyphens, are skipped
+    pub glyph_bytes: Vec<usize>,
+}
+
+/// Lays out the text in `rect`, producing a run of glyphs for each part of a span on a line
+/// with a single direction. Lines are in the same order as the text, and the runs within each
+/// line are in display order, see `bidi::layout_line`.
+pub fn get_glyph_runs(text: &str,
+                      spans: &[Span],
+                      rect: Rect,
+                      line_wrap: Wrap,
+                      align: Align)
+                      -> Vec<GlyphRun>
+{
+    let line_infos: Vec<LineInfo> = LineInfos::with_spans(text, spans, line_wrap, rect.width())
+        .collect();
+    let fonts = Fonts::Spans(spans);
+
+    let mut runs = Vec::new();
+    let mut top = rect.top();
+    for line_info in line_infos {
+        let metrics = line_metrics(spans, line_info.byte_range());
+        let baseline = top + metrics.ascent;
+        let line_x = line::align_line(&line_info, align, rect.x_range());
+        let x = line_x.start;
+
+        let line_text = &text[line_info.byte_range()];
+        let word_spacing = match align {
+            Align::Justify => line::justified_word_spacing(line_text, &line_info, line_x.end - line_x.start),
+            _ => 0.0,
+        };
+        let bidi = BidiLine::new(line_text);
+        let mut run: Option<GlyphRun> = None;
+        let mut last_char = None;
+        for g in bidi::layout_line(line_text, &bidi, fonts, line_info.start_byte, word_spacing) {
+            let rtl = bidi.is_rtl_char(g.char_i);
+            // a run continues while the span and direction stay the same and the chars follow
+            // on from each other in the text
+            let continues = run.as_ref().map_or(false, |run| run.span == g.span && run.rtl == rtl) &&
+                last_char.map_or(false, |last_char| {
+                    if rtl { g.char_i + 1 == last_char } else { last_char + 1 == g.char_i }
+                });
+            let start_byte = line_info.start_byte + g.byte_i;
+            let end_byte = start_byte + line_text[g.byte_i..].chars().next().unwrap().len_utf8();
+            if !continues {
+                runs.extend(run.take());
+                run = Some(GlyphRun {
+                    span: g.span,
+                    byte_range: start_byte..end_byte,
+                    rtl: rtl,
+                    rect: Rect::new(Point::new(x + g.x, top), Size::new(0.0, metrics.height)),
+                    baseline: baseline,
+                    glyphs: Vec::new(),
+                    glyph_bytes: Vec::new(),
+                });
+            }
+            last_char = Some(g.char_i);
+            let advance_width = g.glyph.h_metrics().advance_width;
+
+            let run = run.as_mut().unwrap();
+            run.glyphs.push(g.glyph.positioned(rusttype::Point { x: x + g.x, y: baseline }).standalone());
+            run.glyph_bytes.push(start_byte);
+            run.rect.size.width = x + g.x + advance_width - run.rect.left();
+            run.byte_range.start = cmp::min(run.byte_range.start, start_byte);
+            run.byte_range.end = cmp::max(run.byte_range.end, end_byte);
+        }
+        runs.extend(run);
+        top += metrics.height;
+    }
+    runs
+}
+
+/// The byte index of the character under `point`, if any, for text laid out by `get_glyph_runs`
+pub fn byte_index_at(runs: &[GlyphRun], point: Point) -> Option<usize> {
+    let run = runs.iter().find(|run| run.rect.x_range().is_over(point.x) && run.rect.y_range().is_over(point.y));
+    run.and_then(|run| {
+        let glyph_ends = run.glyphs.iter().skip(1).map(|glyph| glyph.position().x)
+            .chain(Some(run.rect.right()));
+        run.glyph_bytes.iter().zip(glyph_ends)
+            .find(|&(_, glyph_end)| point.x <= glyph_end)
+            .map(|(&byte_i, _)| byte_i)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FONT_SIZE: f32 = 20.0;
+
+    fn overflow(text: &str, font: &Font, wrap: Wrap, overflow: Overflow, size: Size) -> String {
+        let spans = [Span { range: 0..text.len(), font: font, font_size: FONT_SIZE }];
+        overflow_text(text, &spans, wrap, overflow, size)
+    }
+
+    fn width(text: &str, font: &Font) -> f32 {
+        line::width(text, font, FONT_SIZE)
+    }
+
+    fn line_height(font: &Font) -> f32 {
+        let spans = [Span { range: 0..1, font: font, font_size: FONT_SIZE }];
+        line_metrics(&spans, 0..1).height
+    }
+
+    // has no glyph for some characters that the test font has, like U+2665 BLACK HEART SUIT
+    fn hack_font() -> Font {
+        let data: &'static [u8] = include_bytes!("../../assets/fonts/Hack/Hack-Regular.ttf");
+        rusttype::FontCollection::from_bytes(data).into_font().unwrap()
+    }
+
+    fn glyph_runs<'a>(text: &str, spans: &[Span<'a>]) -> Vec<GlyphRun> {
+        let rect = Rect::new(Point::new(0.0, 0.0), Size::new(1000.0, 1000.0));
+        get_glyph_runs(text, spans, rect, Wrap::Whitespace, Align::Start)
+    }
+
+    #[test]
+    fn fitting_text_unchanged() {
+        let font = ::test_font();
+        let size = Size::new(1000.0, 1000.0);
+        for &mode in &[Overflow::Clip, Overflow::EllipsisEnd, Overflow::EllipsisMiddle, Overflow::MaxLines(2)] {
+            assert_eq!(overflow("Hello world", &font, Wrap::Whitespace, mode, size), "Hello world");
+        }
+        let size = Size::new(1000.0, line_height(&font) * 2.5);
+        assert_eq!(overflow("one\ntwo", &font, Wrap::Whitespace, Overflow::EllipsisEnd, size), "one\ntwo");
+        assert_eq!(overflow("one\ntwo", &font, Wrap::Whitespace, Overflow::MaxLines(2), size), "one\ntwo");
+    }
+
+    #[test]
+    fn ellipsis_end() {
+        let font = ::test_font();
+        let ellipsis = width("\u{2026}", &font);
+        let size = Size::new(width("Hello", &font) + ellipsis + 1.0, 1000.0);
+        assert_eq!(overflow("Hello world", &font, Wrap::NoWrap, Overflow::EllipsisEnd, size), "Hello\u{2026}");
+        // the last line that fits in the height is cut off, even if it fits the width
+        let size = Size::new(width("one two", &font) + ellipsis + 1.0, line_height(&font) * 1.5);
+        assert_eq!(overflow("one two three", &font, Wrap::Whitespace, Overflow::EllipsisEnd, size),
+                   "one two\u{2026}");
+        // the first line is kept even if it doesn't fit in the height
+        let size = Size::new(1000.0, 1.0);
+        assert_eq!(overflow("one\ntwo", &font, Wrap::Whitespace, Overflow::EllipsisEnd, size), "one\u{2026}");
+    }
+
+    #[test]
+    fn ellipsis_middle() {
+        let font = ::test_font();
+        let text = "abcdefghijkl\nmore";
+        let size = Size::new(width("abcdefgh", &font), 1000.0);
+        let shown = overflow(text, &font, Wrap::Whitespace, Overflow::EllipsisMiddle, size);
+        let (start, end) = shown.split_at(shown.find('\u{2026}').unwrap());
+        let end = &end['\u{2026}'.len_utf8()..];
+        assert!(!start.is_empty() && "abcdefghijkl".starts_with(start));
+        assert!(!end.is_empty() && "abcdefghijkl".ends_with(end));
+        assert!(start.len() + end.len() < 12);
+        assert!(width(&shown, &font) <= size.width);
+        // the first line is shortened even when it fits, to drop the later lines
+        let size = Size::new(1000.0, 1000.0);
+        assert_eq!(overflow("ab\ncd", &font, Wrap::Whitespace, Overflow::EllipsisMiddle, size), "ab\u{2026}");
+    }
+
+    #[test]
+    fn max_lines() {
+        let font = ::test_font();
+        let size = Size::new(1000.0, 1000.0);
+        let text = "one\ntwo\nthree";
+        assert_eq!(overflow(text, &font, Wrap::Whitespace, Overflow::MaxLines(2), size), "one\ntwo\u{2026}");
+        assert_eq!(overflow(text, &font, Wrap::Whitespace, Overflow::MaxLines(3), size), text);
+        assert_eq!(overflow(text, &font, Wrap::Whitespace, Overflow::MaxLines(0), size), "one\u{2026}");
+        // wrapped lines count too
+        let size = Size::new(width("one two", &font) + 1.0, 1000.0);
+        assert_eq!(overflow("one two three four", &font, Wrap::Unicode, Overflow::MaxLines(2), size),
+                   "one two\nthree\u{2026}");
+    }
+
+    #[test]
+    fn fit_width_at_boundaries() {
+        let advances = [(0, 10.0), (1, 10.0), (2, 10.0)];
+        assert_eq!(fit_width(&advances, &[0, 1, 2, 3], 3, 25.0), 2);
+        assert_eq!(fit_width(&advances, &[0, 1, 2, 3], 3, 30.0), 3);
+        // a grapheme of the first two chars
+        assert_eq!(fit_width(&advances, &[0, 2, 3], 3, 15.0), 0);
+        assert_eq!(fit_width(&[], &[0], 0, 10.0), 0);
+    }
+
+    #[test]
+    fn split_by_font_fallback() {
+        let (hack, noto) = (hack_font(), ::test_font());
+        assert!(!has_glyph(&hack, '\u{2665}') && has_glyph(&noto, '\u{2665}'));
+        // the spaces stay with the heart, rather than switching back to the first font
+        assert_eq!(split_by_font("a \u{2665} b", &[&hack, &noto]), vec![(0..2, 0), (2..6, 1), (6..7, 0)]);
+        // neither font has hebrew letters, so the first font is used
+        assert_eq!(split_by_font("\u{2665}\u{5D0}", &[&hack, &noto]), vec![(0..3, 1), (3..5, 0)]);
+        assert_eq!(split_by_font("\u{5D0}", &[&noto, &hack]), vec![(0..2, 0)]);
+        assert_eq!(split_by_font("", &[&hack, &noto]), vec![(0..0, 0)]);
+    }
+
+    #[test]
+    fn byte_index_at_link() {
+        let font = ::test_font();
+        let text = "see link here";
+        let spans = [
+            Span { range: 0..4, font: &font, font_size: FONT_SIZE },
+            Span { range: 4..8, font: &font, font_size: FONT_SIZE },
+            Span { range: 8..13, font: &font, font_size: FONT_SIZE },
+        ];
+        let runs = glyph_runs(text, &spans);
+        let link = runs.iter().find(|run| run.span == 1).unwrap();
+        assert_eq!(link.byte_range, 4..8);
+        let y = link.rect.y_range().middle();
+        let on_link = byte_index_at(&runs, Point::new(link.rect.left() + 1.0, y));
+        assert_eq!(on_link, Some(4));
+        let on_link = byte_index_at(&runs, Point::new(link.rect.right() - 1.0, y));
+        assert_eq!(on_link, Some(7));
+        // the spaces around the link belong to the neighbouring spans
+        assert_eq!(byte_index_at(&runs, Point::new(link.rect.left() - 1.0, y)), Some(3));
+        assert_eq!(byte_index_at(&runs, Point::new(link.rect.right() + 1.0, y)), Some(8));
+    }
+
+    #[test]
+    fn byte_index_at_lines() {
+        let font = ::test_font();
+        let text = "one\ntwo three";
+        let spans = [Span { range: 0..text.len(), font: &font, font_size: FONT_SIZE }];
+        let runs = glyph_runs(text, &spans);
+        let height = line_height(&font);
+        // lines are stacked without gaps, the bottom edge of a line belongs to the line above
+        assert_eq!(byte_index_at(&runs, Point::new(1.0, height)), Some(0));
+        assert_eq!(byte_index_at(&runs, Point::new(1.0, height + 1.0)), Some(4));
+        // past the end of a shorter line, or below the last line
+        assert_eq!(byte_index_at(&runs, Point::new(width("one two", &font), height / 2.0)), None);
+        assert_eq!(byte_index_at(&runs, Point::new(1.0, height * 2.0 + 1.0)), None);
+    }
+
+    #[test]
+    fn byte_index_at_rtl() {
+        let font = ::test_font();
+        let text = "\u{5D0}\u{5D1}\u{5D2}";
+        let spans = [Span { range: 0..text.len(), font: &font, font_size: FONT_SIZE }];
+        let runs = glyph_runs(text, &spans);
+        assert_eq!(runs.len(), 1);
+        let rect = runs[0].rect;
+        let y = rect.y_range().middle();
+        // the first letter is drawn at the right
+        assert_eq!(byte_index_at(&runs, Point::new(rect.right() - 1.0, y)), Some(0));
+        assert_eq!(byte_index_at(&runs, Point::new(rect.left() + 1.0, y)), Some(4));
+    }
+
+    #[test]
+    fn kerning_agrees() {
+        use cursor;
+
+        // the bundled fonts only kern through GPOS, which rusttype doesn't read, so this checks
+        // that the measurements agree rather than the amount of kerning
+        let font = ::test_font();
+        let text = "AV";
+        let spans = [Span { range: 0..text.len(), font: &font, font_size: FONT_SIZE }];
+        let infos: Vec<LineInfo> = LineInfos::with_spans(text, &spans, Wrap::NoWrap, 1000.0).collect();
+        let runs = glyph_runs(text, &spans);
+        let rect = Rect::new(Point::new(0.0, 0.0), Size::new(1000.0, 1000.0));
+        let (xs, _) = cursor::xys_per_line_from_text(text, &infos, &font, FONT_SIZE, Align::Start,
+                                                     line_height(&font), rect).next().unwrap();
+        let xs: Vec<f32> = xs.collect();
+
+        let glyph_xs: Vec<f32> = runs[0].glyphs.iter().map(|glyph| glyph.position().x).collect();
+        assert_eq!(infos[0].width, width(text, &font));
+        assert_eq!(xs.len(), 3);
+        assert!((xs[0] - glyph_xs[0]).abs() < 0.01);
+        assert!((xs[1] - glyph_xs[1]).abs() < 0.01);
+        assert!((xs[2] - infos[0].width).abs() < 0.01);
+        assert!((runs[0].rect.width() - infos[0].width).abs() < 0.01);
+    }
+}
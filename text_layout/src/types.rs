@@ -56,6 +56,9 @@ pub enum Align {
     Middle,
     /// **Align** our **End** with the **End** of some other widget along the **Axis**.
     End,
+    /// Stretch the space between words so that wrapped lines fill the width, the last line
+    /// of each paragraph is aligned to the **Start**.
+    Justify,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]